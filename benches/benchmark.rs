@@ -1,6 +1,6 @@
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{thread_rng, Rng};
 
 fn bench_version_sort() {
     let expected = vec![
@@ -82,8 +82,56 @@ fn bench_version_sort() {
     vsort::sort(&mut list);
 }
 
+// large_input builds a shuffled package index similar to the ones found in distro mirrors.
+fn large_input(len: usize) -> Vec<String> {
+    let names = ["gcc", "glibc", "libstdc++-devel", "nss_ldap", "python3"];
+    let suffixes = ["", "~rc1", "a", ".fc9"];
+    let extensions = [".tar.gz", ".rpm", ".deb", ""];
+    let mut rng = thread_rng();
+    (0..len)
+        .map(|_| {
+            format!(
+                "{}-{}.{}.{}{}{}",
+                names.choose(&mut rng).unwrap(),
+                rng.gen_range(0..20),
+                rng.gen_range(0..100),
+                rng.gen_range(0..1000),
+                suffixes.choose(&mut rng).unwrap(),
+                extensions.choose(&mut rng).unwrap(),
+            )
+        })
+        .collect()
+}
+
 pub fn criterion_benchmark(c: &mut Criterion) {
-    c.bench_function("version sort", |b| b.iter(|| bench_version_sort()));
+    c.bench_function("version sort", |b| b.iter(bench_version_sort));
+
+    let input = large_input(200_000);
+    let mut group = c.benchmark_group("large input");
+    group.sample_size(10);
+    group.bench_function("sort", |b| {
+        b.iter_batched_ref(
+            || input.clone(),
+            |list| vsort::sort(list),
+            BatchSize::LargeInput,
+        )
+    });
+    group.bench_function("sort_by_cached_version_key", |b| {
+        b.iter_batched_ref(
+            || input.clone(),
+            |list| vsort::sort_by_cached_version_key(list, |s| s.clone()),
+            BatchSize::LargeInput,
+        )
+    });
+    #[cfg(feature = "rayon")]
+    group.bench_function("par_sort", |b| {
+        b.iter_batched_ref(
+            || input.clone(),
+            |list| vsort::par_sort(list),
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
 }
 
 criterion_group!(benches, criterion_benchmark);
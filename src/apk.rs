@@ -0,0 +1,269 @@
+//! Alpine Linux package version ordering, as implemented by `apk version`.
+//!
+//! apk versions follow the Gentoo format `{number}{.number}...{letter}{_suffix{number}}...{-rN}`.
+//! Versions are ordered by their numbers, then by their optional letter, their suffixes and
+//! finally by their package revision. The suffixes `_alpha`, `_beta`, `_pre` and `_rc` mark
+//! pre-releases that sort before the version without a suffix, while `_cvs`, `_svn`, `_git`,
+//! `_hg` and `_p` mark post-releases that sort after it.
+//! https://wiki.alpinelinux.org/wiki/Package_policies
+use core::cmp::Ordering;
+
+use crate::compare_digit_seq;
+
+// The suffixes in the order they sort in. The first PRE_RELEASE_SUFFIXES of them are
+// pre-releases.
+const SUFFIXES: &[&str] = &["alpha", "beta", "pre", "rc", "cvs", "svn", "git", "hg", "p"];
+const PRE_RELEASE_SUFFIXES: usize = 4;
+
+// Parts is a valid version split into its parts, with each part still in its original spelling.
+struct Parts<'a> {
+    // The numbers, separated by periods.
+    numbers: &'a str,
+    letter: Option<u8>,
+    // The suffixes including their leading underscores, e.g. "_rc1_p2".
+    suffixes: &'a str,
+    // The digits of the package revision, which are empty without one.
+    revision: &'a str,
+}
+
+impl<'a> Parts<'a> {
+    fn parse(s: &'a str) -> Option<Self> {
+        let numbers_len = count_numbers(s)?;
+        let (numbers, rest) = s.split_at(numbers_len);
+
+        let (letter, rest) = match rest.as_bytes().first() {
+            Some(&c) if c.is_ascii_lowercase() => (Some(c), &rest[1..]),
+            _ => (None, rest),
+        };
+
+        let mut suffixes_len = 0;
+        while let Some(suffix) = rest[suffixes_len..].strip_prefix('_') {
+            let name = SUFFIXES.iter().find(|name| {
+                suffix.strip_prefix(*name).is_some_and(|after| {
+                    after.is_empty() || after.starts_with(|c: char| "_-0123456789".contains(c))
+                })
+            })?;
+            let digits = count_digits(&suffix[name.len()..]);
+            suffixes_len += 1 + name.len() + digits;
+        }
+        let (suffixes, rest) = rest.split_at(suffixes_len);
+
+        let revision = match rest.strip_prefix("-r") {
+            Some(revision) if count_digits(revision) > 0 => revision,
+            None if rest.is_empty() => rest,
+            _ => return None,
+        };
+        if count_digits(revision) != revision.len() {
+            return None;
+        }
+
+        Some(Self {
+            numbers,
+            letter,
+            suffixes,
+            revision,
+        })
+    }
+
+    fn compare(&self, other: &Self) -> Ordering {
+        compare_numbers(self.numbers, other.numbers)
+            .then(self.letter.cmp(&other.letter))
+            .then_with(|| compare_suffixes(self.suffixes, other.suffixes))
+            .then_with(|| compare_digit_seq(self.revision.as_bytes(), other.revision.as_bytes()))
+    }
+}
+
+// count_numbers returns the length of the leading numbers separated by periods, or None if s does
+// not start with a number.
+fn count_numbers(s: &str) -> Option<usize> {
+    let mut len = count_digits(s);
+    if len == 0 {
+        return None;
+    }
+    while let Some(rest) = s[len..].strip_prefix('.') {
+        match count_digits(rest) {
+            0 => break,
+            digits => len += 1 + digits,
+        }
+    }
+    Some(len)
+}
+
+fn count_digits(s: &str) -> usize {
+    s.bytes().take_while(|c| c.is_ascii_digit()).count()
+}
+
+// compare_numbers compares the numbers of two versions. The first number is compared by value.
+// Like in Gentoo, the following numbers are compared as strings without their trailing zeros if
+// either of them has a leading zero, so that 1.05 sorts before 1.1. Otherwise they are compared
+// by value. If all numbers are equal, the version with more numbers is newer.
+fn compare_numbers(a: &str, b: &str) -> Ordering {
+    let mut a_numbers = a.split('.');
+    let mut b_numbers = b.split('.');
+    let mut first = true;
+    loop {
+        let (a, b) = match (a_numbers.next(), b_numbers.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => (a, b),
+        };
+        let cmp = if !first && (a.starts_with('0') || b.starts_with('0')) {
+            a.trim_end_matches('0').cmp(b.trim_end_matches('0'))
+        } else {
+            compare_digit_seq(a.as_bytes(), b.as_bytes())
+        };
+        if cmp != Ordering::Equal {
+            return cmp;
+        }
+        first = false;
+    }
+}
+
+// compare_suffixes compares the suffixes of two versions in order. A suffix that the other
+// version does not have makes the version older if it is a pre-release suffix, and newer
+// otherwise.
+fn compare_suffixes(a: &str, b: &str) -> Ordering {
+    let mut a_suffixes = a.split('_').skip(1).map(split_suffix);
+    let mut b_suffixes = b.split('_').skip(1).map(split_suffix);
+    loop {
+        let cmp = match (a_suffixes.next(), b_suffixes.next()) {
+            (None, None) => return Ordering::Equal,
+            (Some((a_rank, _)), None) if a_rank < PRE_RELEASE_SUFFIXES => Ordering::Less,
+            (Some(_), None) => Ordering::Greater,
+            (None, Some((b_rank, _))) if b_rank < PRE_RELEASE_SUFFIXES => Ordering::Greater,
+            (None, Some(_)) => Ordering::Less,
+            (Some((a_rank, a_number)), Some((b_rank, b_number))) => a_rank
+                .cmp(&b_rank)
+                .then_with(|| compare_digit_seq(a_number.as_bytes(), b_number.as_bytes())),
+        };
+        if cmp != Ordering::Equal {
+            return cmp;
+        }
+    }
+}
+
+// split_suffix splits a suffix that was validated by Parts::parse into its rank and number.
+fn split_suffix(suffix: &str) -> (usize, &str) {
+    let name_len = suffix.len() - count_digits_from_end(suffix);
+    let rank = SUFFIXES
+        .iter()
+        .position(|&name| name == &suffix[..name_len])
+        .unwrap_or(SUFFIXES.len());
+    (rank, &suffix[name_len..])
+}
+
+fn count_digits_from_end(s: &str) -> usize {
+    s.bytes().rev().take_while(|c| c.is_ascii_digit()).count()
+}
+
+/// is_valid reports whether the given string is a valid apk version.
+/// # Examples
+/// ```
+/// use vsort::apk;
+///
+/// fn main() {
+///     assert!(apk::is_valid("1.2.3_rc1-r0"));
+///     assert!(!apk::is_valid("1.2.3-rc1"));
+/// }
+/// ```
+pub fn is_valid(s: &str) -> bool {
+    Parts::parse(s).is_some()
+}
+
+/// compare compares two package versions like `apk version -t`.
+///
+/// compare is lenient: it accepts any input and never fails. Valid versions sort before all
+/// other strings, and ties between them, e.g. `1.0` and `1.0-r0`, are broken with GNU version
+/// sort. Strings that are not valid versions are ordered with GNU version sort.
+/// # Examples
+/// ```
+/// use vsort::apk;
+///
+/// fn main() {
+///     let mut versions = vec!["1.0-r1", "1.0_p1-r0", "1.0_rc1-r0", "1.0_alpha2-r0", "1.0a-r0"];
+///     versions.sort_by(|a, b| apk::compare(a, b));
+///     assert_eq!(
+///         versions,
+///         vec!["1.0_alpha2-r0", "1.0_rc1-r0", "1.0-r1", "1.0_p1-r0", "1.0a-r0"]
+///     );
+/// }
+/// ```
+pub fn compare(a: &str, b: &str) -> Ordering {
+    match (Parts::parse(a), Parts::parse(b)) {
+        (Some(a_parts), Some(b_parts)) => {
+            a_parts.compare(&b_parts).then_with(|| crate::compare(a, b))
+        }
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => crate::compare(a, b),
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("1.0", "1.0", Ordering::Equal; "same version")]
+    #[test_case("1.0", "1.0-r0", Ordering::Equal; "missing revision is zero")]
+    #[test_case("1.0-r1", "1.0-r2", Ordering::Less; "revision")]
+    #[test_case("1.0-r9", "1.0-r10", Ordering::Less; "revisions are numbers")]
+    #[test_case("1.0-r5", "1.0.1-r0", Ordering::Less; "numbers beat revision")]
+    #[test_case("1.9", "1.10", Ordering::Less; "numbers")]
+    #[test_case("1.0", "1.0.0", Ordering::Less; "more numbers")]
+    #[test_case("2.34", "0.1.0_alpha", Ordering::Greater; "first number")]
+    #[test_case("1.05", "1.1", Ordering::Less; "leading zero")]
+    #[test_case("1.05", "1.005", Ordering::Greater; "more leading zeros")]
+    #[test_case("1.01", "1.010", Ordering::Equal; "trailing zeros with leading zero")]
+    #[test_case("010", "9", Ordering::Greater; "first number by value")]
+    #[test_case("1.0", "1.0a", Ordering::Less; "letter")]
+    #[test_case("1.0a", "1.0b", Ordering::Less; "letters")]
+    #[test_case("1.0z", "1.0.1", Ordering::Less; "numbers beat letter")]
+    #[test_case("1.0_alpha", "1.0_beta", Ordering::Less; "alpha before beta")]
+    #[test_case("1.0_beta", "1.0_pre", Ordering::Less; "beta before pre")]
+    #[test_case("1.0_pre", "1.0_rc", Ordering::Less; "pre before rc")]
+    #[test_case("1.0_rc", "1.0", Ordering::Less; "rc before release")]
+    #[test_case("1.0", "1.0_cvs", Ordering::Less; "release before cvs")]
+    #[test_case("1.0_cvs", "1.0_svn", Ordering::Less; "cvs before svn")]
+    #[test_case("1.0_svn", "1.0_git", Ordering::Less; "svn before git")]
+    #[test_case("1.0_git", "1.0_hg", Ordering::Less; "git before hg")]
+    #[test_case("1.0_hg", "1.0_p", Ordering::Less; "hg before p")]
+    #[test_case("1.0_alpha", "1.0_alpha0", Ordering::Equal; "missing suffix number is zero")]
+    #[test_case("1.0_rc9", "1.0_rc10", Ordering::Less; "suffix numbers")]
+    #[test_case("1.0_rc1_p1", "1.0_rc1", Ordering::Greater; "extra post suffix")]
+    #[test_case("1.0_rc1_alpha", "1.0_rc1", Ordering::Less; "extra pre suffix")]
+    #[test_case("1.0_p1", "1.0a", Ordering::Less; "letter beats suffix")]
+    #[test_case("1.0_pre1", "1.0_p1", Ordering::Less; "pre is not p")]
+    fn test_parts_compare(a: &str, b: &str, expected: Ordering) {
+        let a = Parts::parse(a).unwrap();
+        let b = Parts::parse(b).unwrap();
+        assert_eq!(a.compare(&b), expected);
+        assert_eq!(b.compare(&a), expected.reverse());
+    }
+
+    #[test_case(""; "empty")]
+    #[test_case("a1.0"; "starts with letter")]
+    #[test_case("1.0-rc1"; "hyphen before suffix")]
+    #[test_case("1.0_foo"; "unknown suffix")]
+    #[test_case("1.0_prefoo"; "suffix followed by letters")]
+    #[test_case("1.0-r"; "empty revision")]
+    #[test_case("1.0-r1a"; "letter in revision")]
+    #[test_case("1.0A"; "uppercase letter")]
+    #[test_case("1.0ab"; "two letters")]
+    #[test_case("1.0."; "trailing period")]
+    #[test_case("1.0a5"; "digits after letter")]
+    #[test_case("1.0_rc1x"; "letter after suffix")]
+    fn test_invalid(input: &str) {
+        assert!(!is_valid(input));
+    }
+
+    #[test_case("1.0", "1.0-r0", Ordering::Less; "tie break with version sort")]
+    #[test_case("1.0", "1.0-rc1", Ordering::Less; "valid before invalid")]
+    #[test_case("1.0_foo", "1.0_bar", Ordering::Greater; "invalid use version sort")]
+    fn test_compare(a: &str, b: &str, expected: Ordering) {
+        assert_eq!(compare(a, b), expected);
+        assert_eq!(compare(b, a), expected.reverse());
+    }
+}
@@ -0,0 +1,224 @@
+//! Arch Linux package version ordering, as implemented by pacman's `vercmp` and
+//! `alpm_pkg_vercmp`.
+//!
+//! A version has the form `[epoch:]pkgver[-pkgrel]`. Versions are ordered by their epoch, then by
+//! their pkgver and finally by their pkgrel. The parts are compared with libalpm's variant of
+//! `rpmvercmp`, which has no tilde or caret rules. Instead, a longer run of separators wins, and a
+//! version that only differs by a trailing alphabetic segment is older, so `1.0rc` is older than
+//! `1.0`.
+//! https://man.archlinux.org/man/vercmp.8
+use core::cmp::Ordering;
+
+use crate::compare_digit_seq;
+use crate::rpm::Evr;
+
+/// vercmp compares two package versions like pacman's `vercmp`.
+///
+/// Like pacman, the pkgrel is only compared if both versions have one, so `1.0` and `1.0-1` are
+/// equal. Use `compare` to sort versions.
+/// # Examples
+/// ```
+/// use std::cmp::Ordering;
+/// use vsort::arch;
+///
+/// fn main() {
+///     assert_eq!(arch::vercmp("1.0rc", "1.0"), Ordering::Less);
+///     assert_eq!(arch::vercmp("1:1.0", "2.0"), Ordering::Greater);
+///     assert_eq!(arch::vercmp("1.5-1", "1.5"), Ordering::Equal);
+/// }
+/// ```
+pub fn vercmp(a: &str, b: &str) -> Ordering {
+    // Another quick shortcut, if the full version specs are equal.
+    if a == b {
+        return Ordering::Equal;
+    }
+    let a = Evr::parse(a);
+    let b = Evr::parse(b);
+    // Unlike RPM, a missing epoch always counts as 0.
+    alpm_rpmvercmp(a.epoch().unwrap_or("0"), b.epoch().unwrap_or("0"))
+        .then_with(|| alpm_rpmvercmp(a.version(), b.version()))
+        .then_with(|| match (a.release(), b.release()) {
+            (Some(a), Some(b)) => alpm_rpmvercmp(a, b),
+            _ => Ordering::Equal,
+        })
+}
+
+/// compare compares two package versions like `vercmp`, except that a version without a pkgrel
+/// sorts before the same version with any pkgrel. This makes compare a total order that can be
+/// used for sorting.
+/// # Examples
+/// ```
+/// use vsort::arch;
+///
+/// fn main() {
+///     let mut versions = vec!["1:0.9-1", "1.5-1", "1.5b-1", "1.5", "1.5.1-1"];
+///     versions.sort_by(|a, b| arch::compare(a, b));
+///     assert_eq!(versions, vec!["1.5b-1", "1.5", "1.5-1", "1.5.1-1", "1:0.9-1"]);
+/// }
+/// ```
+pub fn compare(a: &str, b: &str) -> Ordering {
+    vercmp(a, b).then_with(|| {
+        let a_has_release = Evr::parse(a).release().is_some();
+        a_has_release.cmp(&Evr::parse(b).release().is_some())
+    })
+}
+
+// alpm_rpmvercmp is a port of rpmvercmp from pacman's lib/libalpm/version.c.
+fn alpm_rpmvercmp(a: &str, b: &str) -> Ordering {
+    // Easy comparison to see if versions are identical.
+    if a == b {
+        return Ordering::Equal;
+    }
+    let mut one = a.as_bytes();
+    let mut two = b.as_bytes();
+    // Loop through each version segment of one and two and compare them.
+    while !one.is_empty() && !two.is_empty() {
+        let one_separators = one
+            .iter()
+            .take_while(|c| !c.is_ascii_alphanumeric())
+            .count();
+        let two_separators = two
+            .iter()
+            .take_while(|c| !c.is_ascii_alphanumeric())
+            .count();
+        one = &one[one_separators..];
+        two = &two[two_separators..];
+
+        // If we ran to the end of either, we are finished with the loop.
+        if one.is_empty() || two.is_empty() {
+            break;
+        }
+        // If the separator lengths were different, we are also finished.
+        if one_separators != two_separators {
+            return one_separators.cmp(&two_separators);
+        }
+
+        // Grab the first completely alpha or completely numeric segment of each.
+        let is_num = one[0].is_ascii_digit();
+        let in_segment = |c: &u8| {
+            if is_num {
+                c.is_ascii_digit()
+            } else {
+                c.is_ascii_alphabetic()
+            }
+        };
+        let one_len = one.iter().take_while(|c| in_segment(c)).count();
+        let two_len = two.iter().take_while(|c| in_segment(c)).count();
+        let (one_segment, one_rest) = one.split_at(one_len);
+        let (two_segment, two_rest) = two.split_at(two_len);
+
+        // The segments are of different types: one numeric, the other alpha. Numeric segments
+        // are always newer than alpha segments.
+        if two_segment.is_empty() {
+            return if is_num {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+        }
+
+        let cmp = if is_num {
+            compare_digit_seq(one_segment, two_segment)
+        } else {
+            one_segment.cmp(two_segment)
+        };
+        if cmp != Ordering::Equal {
+            return cmp;
+        }
+
+        one = one_rest;
+        two = two_rest;
+    }
+
+    // The final showdown: a remaining alpha string never beats an empty string. If one is empty
+    // and two is not an alpha, two is newer. If one is an alpha, two is newer. Otherwise one is
+    // newer.
+    match (one.first(), two.first()) {
+        (None, None) => Ordering::Equal,
+        (None, Some(c)) if !c.is_ascii_alphabetic() => Ordering::Less,
+        (Some(c), _) if c.is_ascii_alphabetic() => Ordering::Less,
+        _ => Ordering::Greater,
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    // These tests are lifted from
+    // https://gitlab.archlinux.org/pacman/pacman/-/blob/master/test/util/vercmptest.sh
+    // all similar length, no pkgrel
+    #[test_case("1.5.0", "1.5.0", Ordering::Equal)]
+    #[test_case("1.5.1", "1.5.0", Ordering::Greater)]
+    // mixed length
+    #[test_case("1.5.1", "1.5", Ordering::Greater)]
+    // with pkgrel, simple
+    #[test_case("1.5.0-1", "1.5.0-1", Ordering::Equal)]
+    #[test_case("1.5.0-1", "1.5.0-2", Ordering::Less)]
+    #[test_case("1.5.0-1", "1.5.1-1", Ordering::Less)]
+    #[test_case("1.5.0-2", "1.5.1-1", Ordering::Less)]
+    // with pkgrel, mixed lengths
+    #[test_case("1.5-1", "1.5.1-1", Ordering::Less)]
+    #[test_case("1.5-2", "1.5.1-1", Ordering::Less)]
+    #[test_case("1.5-2", "1.5.1-2", Ordering::Less)]
+    // mixed pkgrel inclusion
+    #[test_case("1.5", "1.5-1", Ordering::Equal)]
+    #[test_case("1.5-1", "1.5", Ordering::Equal)]
+    #[test_case("1.1-1", "1.1", Ordering::Equal)]
+    #[test_case("1.0-1", "1.1", Ordering::Less)]
+    #[test_case("1.1-1", "1.0", Ordering::Greater)]
+    // alphanumeric versions
+    #[test_case("1.5b-1", "1.5-1", Ordering::Less)]
+    #[test_case("1.5b", "1.5", Ordering::Less)]
+    #[test_case("1.5b-1", "1.5", Ordering::Less)]
+    #[test_case("1.5b", "1.5.1", Ordering::Less)]
+    // from the manpage
+    #[test_case("1.0a", "1.0alpha", Ordering::Less)]
+    #[test_case("1.0alpha", "1.0b", Ordering::Less)]
+    #[test_case("1.0b", "1.0beta", Ordering::Less)]
+    #[test_case("1.0beta", "1.0rc", Ordering::Less)]
+    #[test_case("1.0rc", "1.0", Ordering::Less)]
+    // going crazy? alpha-dotted versions
+    #[test_case("1.5.a", "1.5", Ordering::Greater)]
+    #[test_case("1.5.b", "1.5.a", Ordering::Greater)]
+    #[test_case("1.5.1", "1.5.b", Ordering::Greater)]
+    // alpha dots and dashes
+    #[test_case("1.5.b-1", "1.5.b", Ordering::Equal)]
+    #[test_case("1.5-1", "1.5.b", Ordering::Less)]
+    // same/similar content, differing separators
+    #[test_case("2.0", "2_0", Ordering::Equal)]
+    #[test_case("2.0_a", "2_0.a", Ordering::Equal)]
+    #[test_case("2.0a", "2.0.a", Ordering::Less)]
+    #[test_case("2___a", "2_a", Ordering::Greater)]
+    // epoch included version comparisons
+    #[test_case("0:1.0", "0:1.0", Ordering::Equal)]
+    #[test_case("0:1.0", "0:1.1", Ordering::Less)]
+    #[test_case("1:1.0", "0:1.0", Ordering::Greater)]
+    #[test_case("1:1.0", "0:1.1", Ordering::Greater)]
+    #[test_case("1:1.0", "2:1.1", Ordering::Less)]
+    // epoch + sometimes present pkgrel
+    #[test_case("1:1.0", "0:1.0-1", Ordering::Greater)]
+    #[test_case("1:1.0-1", "0:1.1-1", Ordering::Greater)]
+    // epoch included on one version
+    #[test_case("0:1.0", "1.0", Ordering::Equal)]
+    #[test_case("0:1.0", "1.1", Ordering::Less)]
+    #[test_case("0:1.1", "1.0", Ordering::Greater)]
+    #[test_case("1:1.0", "1.0", Ordering::Greater)]
+    #[test_case("1:1.0", "1.1", Ordering::Greater)]
+    #[test_case("1:1.1", "1.1", Ordering::Greater)]
+    fn test_vercmp(a: &str, b: &str, expected: Ordering) {
+        assert_eq!(vercmp(a, b), expected);
+        assert_eq!(vercmp(b, a), expected.reverse());
+    }
+
+    #[test_case("1.5", "1.5-1", Ordering::Less; "missing pkgrel sorts first")]
+    #[test_case("1.5.b", "1.5.b-1", Ordering::Less; "missing pkgrel after alpha")]
+    #[test_case("0:1.0", "1.0", Ordering::Equal; "zero epoch")]
+    #[test_case("1.0-2", "1.0-10", Ordering::Less; "pkgrel")]
+    fn test_compare(a: &str, b: &str, expected: Ordering) {
+        assert_eq!(compare(a, b), expected);
+        assert_eq!(compare(b, a), expected.reverse());
+    }
+}
@@ -0,0 +1,621 @@
+//! vsort writes the lines of the given files, or of standard input, sorted with GNU version sort.
+//! It behaves like `sort -V` from GNU coreutils and compares lines by their raw bytes, so lines
+//! that are not valid UTF-8 are kept as they are.
+use std::ffi::{OsStr, OsString};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+
+use vsort::comparator::from_fn;
+use vsort::{ExternalSorter, Key, KeySpec, VersionComparator, VersionSortOptions};
+
+const USAGE: &str = "\
+Usage: vsort [OPTION]... [FILE]...
+Write the lines of all FILEs to standard output, sorted with GNU version sort.
+
+With no FILE, or when FILE is -, read standard input.
+
+  -k, --key=KEYDEF         sort via a key; KEYDEF gives location and type
+  -m, --merge              merge already sorted files; do not sort
+  -o, --output=FILE        write the result to FILE instead of standard output
+  -r, --reverse            reverse the result of comparisons
+  -S, --buffer-size=SIZE   use SIZE for main memory buffer
+  -t, --field-separator=SEP  use SEP instead of non-blank to blank transition
+  -T, --temporary-directory=DIR  use DIR for temporaries, not $TMPDIR or /tmp
+  -u, --unique             output only the first of lines that compare equal
+  -V, --version-sort       sort by version; this is always on
+  -z, --zero-terminated    line delimiter is NUL, not newline
+      --help               display this help and exit
+
+KEYDEF is F[.C][OPTS][,F[.C][OPTS]] for start and stop position, where F is a
+field number and C a character position in the field; both are origin 1, and
+the stop position defaults to the line's end. OPTS is one or more of b, n, r
+and V, which override the global ordering options for that key. A key with
+options but neither n nor V compares bytes. Lines with equal keys are ordered
+by their bytes as a last resort.
+
+SIZE may be followed by the following multiplicative suffixes: b 1, K 1024
+(default), and so on for M, G, T, P and E. Without -S, all lines are sorted in
+memory; with it, sorted runs that exceed SIZE are written to temporary files
+and merged.
+";
+
+// Config holds the parsed command-line arguments.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Config {
+    reverse: bool,
+    unique: bool,
+    merge: bool,
+    zero_terminated: bool,
+    separator: Option<u8>,
+    keys: Vec<Key>,
+    buffer_size: Option<usize>,
+    temp_dir: Option<OsString>,
+    output: Option<OsString>,
+    files: Vec<OsString>,
+}
+
+impl Config {
+    fn delimiter(&self) -> u8 {
+        if self.zero_terminated {
+            b'\0'
+        } else {
+            b'\n'
+        }
+    }
+
+    // comparator returns the comparator for the lines. Like GNU sort, --unique disables the
+    // last-resort byte comparison, so lines that are equal as versions, e.g. a1 and a01, are
+    // duplicates and the first of them in input order is kept.
+    fn comparator(&self) -> Box<dyn VersionComparator> {
+        if self.keys.is_empty() {
+            return Box::new(
+                VersionSortOptions::new()
+                    .reverse(self.reverse)
+                    .byte_tie_break(!self.unique)
+                    .build(),
+            );
+        }
+        let mut spec = KeySpec::new().reverse(self.reverse);
+        if let Some(separator) = self.separator {
+            spec = spec.separator(separator);
+        }
+        for key in &self.keys {
+            spec = spec.key(*key);
+        }
+        let last_resort = from_fn(<[u8]>::cmp);
+        match (self.unique, self.reverse) {
+            (true, _) => Box::new(spec),
+            (false, false) => Box::new(spec.then_with(last_resort)),
+            (false, true) => Box::new(spec.then_with(last_resort.reverse())),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+    Help,
+    Sort(Config),
+}
+
+// parse_args parses the arguments that follow the program name. Like GNU sort, short options can
+// be grouped, e.g. -ru, and option values can be attached, e.g. -oFILE or --output=FILE.
+fn parse_args<I: IntoIterator<Item = OsString>>(args: I) -> Result<Command, String> {
+    let mut config = Config::default();
+    let mut args = args.into_iter();
+    let mut only_files = false;
+    while let Some(arg) = args.next() {
+        if only_files || arg == "-" || !arg.to_string_lossy().starts_with('-') {
+            config.files.push(arg);
+            continue;
+        }
+        let arg = arg
+            .into_string()
+            .map_err(|arg| format!("invalid option -- '{}'", arg.to_string_lossy()))?;
+        if arg == "--" {
+            only_files = true;
+        } else if let Some(long) = arg.strip_prefix("--") {
+            let (name, value) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (long, None),
+            };
+            let option = match name {
+                "buffer-size" => 'S',
+                "field-separator" => 't',
+                "help" => 'h',
+                "key" => 'k',
+                "merge" => 'm',
+                "output" => 'o',
+                "reverse" => 'r',
+                "temporary-directory" => 'T',
+                "unique" => 'u',
+                "version-sort" => 'V',
+                "zero-terminated" => 'z',
+                _ => return Err(format!("unrecognized option '{}'", arg)),
+            };
+            if takes_value(option) {
+                let value = match value {
+                    Some(value) => OsString::from(value),
+                    None => args
+                        .next()
+                        .ok_or_else(|| format!("option '--{}' requires an argument", name))?,
+                };
+                set_value(&mut config, option, value)?;
+            } else if value.is_some() {
+                return Err(format!("option '--{}' doesn't allow an argument", name));
+            } else if !set_flag(&mut config, option) {
+                return Ok(Command::Help);
+            }
+        } else {
+            for (i, option) in arg[1..].char_indices() {
+                if takes_value(option) {
+                    let value = &arg[1 + i + option.len_utf8()..];
+                    let value = if value.is_empty() {
+                        args.next()
+                            .ok_or_else(|| format!("option requires an argument -- '{}'", option))?
+                    } else {
+                        OsString::from(value)
+                    };
+                    set_value(&mut config, option, value)?;
+                    break;
+                }
+                // -h is --human-numeric-sort in GNU sort, so it is not a short form of --help.
+                if !matches!(option, 'm' | 'r' | 'u' | 'V' | 'z') {
+                    return Err(format!("invalid option -- '{}'", option));
+                }
+                if !set_flag(&mut config, option) {
+                    return Ok(Command::Help);
+                }
+            }
+        }
+    }
+    Ok(Command::Sort(config))
+}
+
+fn takes_value(option: char) -> bool {
+    matches!(option, 'k' | 'o' | 'S' | 't' | 'T')
+}
+
+// set_flag applies an option without a value. It returns false for the help option.
+fn set_flag(config: &mut Config, option: char) -> bool {
+    match option {
+        'h' => return false,
+        'm' => config.merge = true,
+        'r' => config.reverse = true,
+        'u' => config.unique = true,
+        'z' => config.zero_terminated = true,
+        _ => {}
+    }
+    true
+}
+
+fn set_value(config: &mut Config, option: char, value: OsString) -> Result<(), String> {
+    match option {
+        'k' => {
+            let spec = value.to_string_lossy();
+            let key = spec
+                .parse()
+                .map_err(|err| format!("{}: invalid field specification '{}'", err, spec))?;
+            config.keys.push(key);
+        }
+        'o' => config.output = Some(value),
+        'S' => {
+            let size = value.to_string_lossy();
+            let size =
+                parse_size(&size).ok_or_else(|| format!("invalid -S argument '{}'", size))?;
+            config.buffer_size = Some(size);
+        }
+        't' => {
+            let separator = match value.as_encoded_bytes() {
+                [] => return Err("empty tab".to_string()),
+                // Like GNU sort, "\0" selects the NUL byte.
+                b"\\0" => b'\0',
+                &[separator] => separator,
+                _ => return Err(format!("multi-character tab '{}'", value.to_string_lossy())),
+            };
+            if config
+                .separator
+                .is_some_and(|previous| previous != separator)
+            {
+                return Err("incompatible tabs".to_string());
+            }
+            config.separator = Some(separator);
+        }
+        'T' => config.temp_dir = Some(value),
+        _ => unreachable!("option -{} does not take a value", option),
+    }
+    Ok(())
+}
+
+// parse_size parses the argument of --buffer-size. Like GNU sort, a number without a suffix
+// counts kibibytes, and sizes too large for memory are capped.
+fn parse_size(size: &str) -> Option<usize> {
+    let digits = size.bytes().take_while(u8::is_ascii_digit).count();
+    if digits == 0 {
+        return None;
+    }
+    let power = match &size[digits..] {
+        "b" => 0,
+        "" | "k" | "K" => 1,
+        "m" | "M" => 2,
+        "g" | "G" => 3,
+        "t" | "T" => 4,
+        "p" | "P" => 5,
+        "e" | "E" => 6,
+        _ => return None,
+    };
+    let number = size[..digits].parse().unwrap_or(usize::MAX);
+    Some(number.saturating_mul(1024usize.saturating_pow(power)))
+}
+
+// run sorts or merges the input selected by config and writes the result. Standard input is only
+// read if no files are given or a file is "-".
+fn run<R: Read, W: Write>(config: &Config, stdin: R, mut stdout: W) -> io::Result<()> {
+    let mut stdin = Some(stdin);
+    let mut inputs = Vec::new();
+    if config.files.is_empty() {
+        inputs.push(open_input(&mut stdin, OsStr::new("-"))?);
+    }
+    for file in &config.files {
+        inputs.push(open_input(&mut stdin, file)?);
+    }
+
+    let mut sorter = ExternalSorter::new(config.buffer_size.unwrap_or(usize::MAX))
+        .delimiter(config.delimiter())
+        .unique(config.unique)
+        .comparator(config.comparator());
+    if let Some(temp_dir) = &config.temp_dir {
+        sorter = sorter.temp_dir(temp_dir);
+    }
+    // A merge writes while it reads, so if it would overwrite one of its inputs, sort instead. The
+    // inputs are sorted, so the result is the same.
+    let merge = config.merge && !overwrites_input(config);
+    let write = |output: &mut dyn Write| {
+        if merge {
+            sorter.merge(inputs, output)
+        } else {
+            sorter.sort_all(inputs, output)
+        }
+    };
+    match &config.output {
+        Some(path) => {
+            let mut output = OutputFile { path, file: None };
+            write(&mut output)?;
+            // Create the file even if there are no lines to write.
+            output.file().map(|_| ())
+        }
+        None => write(&mut stdout),
+    }
+}
+
+// overwrites_input returns true if the output file is also one of the input files.
+fn overwrites_input(config: &Config) -> bool {
+    let output = match config.output.as_ref().map(std::fs::canonicalize) {
+        Some(Ok(output)) => output,
+        _ => return false,
+    };
+    config
+        .files
+        .iter()
+        .any(|file| std::fs::canonicalize(file).is_ok_and(|file| file == output))
+}
+
+// Input is one of the inputs to sort.
+enum Input<'a, R> {
+    Stdin(R),
+    File(File, &'a OsStr),
+    Empty,
+}
+
+impl<R: Read> Read for Input<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Input::Stdin(stdin) => stdin.read(buf),
+            Input::File(file, name) => file.read(buf).map_err(|err| annotate(err, name)),
+            Input::Empty => Ok(0),
+        }
+    }
+}
+
+fn open_input<'a, R: Read>(stdin: &mut Option<R>, file: &'a OsStr) -> io::Result<Input<'a, R>> {
+    if file == "-" {
+        // Standard input can only be read once, later mentions of it are empty.
+        return Ok(stdin.take().map_or(Input::Empty, Input::Stdin));
+    }
+    let input = File::open(file).map_err(|err| annotate(err, file))?;
+    Ok(Input::File(input, file))
+}
+
+fn annotate(err: io::Error, file: &OsStr) -> io::Error {
+    io::Error::new(
+        err.kind(),
+        format!("cannot read: {}: {}", file.to_string_lossy(), err),
+    )
+}
+
+// OutputFile creates the output file on the first write. A sort only writes once all input has
+// been read, so the output file may be one of the input files.
+struct OutputFile<'a> {
+    path: &'a OsStr,
+    file: Option<File>,
+}
+
+impl OutputFile<'_> {
+    fn file(&mut self) -> io::Result<&mut File> {
+        match &mut self.file {
+            Some(file) => Ok(file),
+            file => Ok(file.insert(File::create(self.path)?)),
+        }
+    }
+}
+
+impl Write for OutputFile<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file()?.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.file {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let config = match parse_args(std::env::args_os().skip(1)) {
+        Ok(Command::Help) => {
+            print!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Ok(Command::Sort(config)) => config,
+        Err(err) => {
+            eprintln!("vsort: {}\nTry 'vsort --help' for more information.", err);
+            return ExitCode::from(2);
+        }
+    };
+    match run(&config, io::stdin().lock(), io::stdout().lock()) {
+        Ok(()) => ExitCode::SUCCESS,
+        // Like other filters, exit quietly when the reader of the output goes away.
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => ExitCode::from(2),
+        Err(err) => {
+            eprintln!("vsort: {}", err);
+            ExitCode::from(2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    fn sorted(config: Config, input: &[u8]) -> Vec<u8> {
+        let mut output = Vec::new();
+        run(&config, input, &mut output).unwrap();
+        output
+    }
+
+    #[test_case(&[], Config::default(); "no arguments")]
+    #[test_case(
+        &["-ru", "a", "-", "b"],
+        Config { reverse: true, unique: true, files: args(&["a", "-", "b"]), ..Config::default() };
+        "grouped flags"
+    )]
+    #[test_case(
+        &["-mu", "--merge"],
+        Config { unique: true, merge: true, ..Config::default() };
+        "merge"
+    )]
+    #[test_case(
+        &["--reverse", "--zero-terminated", "--version-sort"],
+        Config { reverse: true, zero_terminated: true, ..Config::default() };
+        "long flags"
+    )]
+    #[test_case(
+        &["-o", "out"],
+        Config { output: Some("out".into()), ..Config::default() };
+        "separate output"
+    )]
+    #[test_case(
+        &["-zVoout", "in"],
+        Config { zero_terminated: true, output: Some("out".into()), files: args(&["in"]), ..Config::default() };
+        "attached output"
+    )]
+    #[test_case(
+        &["--output=out"],
+        Config { output: Some("out".into()), ..Config::default() };
+        "long output"
+    )]
+    #[test_case(
+        &["-t:", "-k2,2n", "--key", "1", "--field-separator=:"],
+        Config {
+            separator: Some(b':'),
+            keys: vec!["2,2n".parse().unwrap(), Key::new(1)],
+            ..Config::default()
+        };
+        "keys"
+    )]
+    #[test_case(
+        &["-rk", "2", "-t", "\\0"],
+        Config { reverse: true, separator: Some(b'\0'), keys: vec![Key::new(2)], ..Config::default() };
+        "grouped key and nul separator"
+    )]
+    #[test_case(
+        &["-S", "10M", "--temporary-directory=tmp", "-uS1b"],
+        Config { unique: true, buffer_size: Some(1), temp_dir: Some("tmp".into()), ..Config::default() };
+        "buffer size and temporary directory"
+    )]
+    #[test_case(
+        &["--", "-r", "--help"],
+        Config { files: args(&["-r", "--help"]), ..Config::default() };
+        "double dash"
+    )]
+    fn test_parse_args(input: &[&str], expected: Config) {
+        assert_eq!(parse_args(args(input)), Ok(Command::Sort(expected)));
+    }
+
+    #[test_case(&["-x"], "invalid option -- 'x'"; "unknown flag")]
+    #[test_case(&["--bogus"], "unrecognized option '--bogus'"; "unknown long option")]
+    #[test_case(&["-o"], "option requires an argument -- 'o'"; "missing output")]
+    #[test_case(&["--output"], "option '--output' requires an argument"; "missing long output")]
+    #[test_case(&["--reverse=yes"], "option '--reverse' doesn't allow an argument"; "flag with value")]
+    #[test_case(&["-k0"], "field number is zero: invalid field specification '0'"; "invalid key")]
+    #[test_case(&["-t", ""], "empty tab"; "empty separator")]
+    #[test_case(&["-tab"], "multi-character tab 'ab'"; "long separator")]
+    #[test_case(&["-t:", "-t,"], "incompatible tabs"; "two separators")]
+    #[test_case(&["-S", "10X"], "invalid -S argument '10X'"; "invalid size")]
+    #[test_case(&["--buffer-size=50%"], "invalid -S argument '50%'"; "percent size")]
+    fn test_parse_args_errors(input: &[&str], expected: &str) {
+        assert_eq!(parse_args(args(input)), Err(expected.to_string()));
+    }
+
+    #[test_case("1b", Some(1); "bytes")]
+    #[test_case("2", Some(2048); "default kibibytes")]
+    #[test_case("3k", Some(3072); "lowercase suffix")]
+    #[test_case("1G", Some(1 << 30); "gibibytes")]
+    #[test_case("99999999999999999999999E", Some(usize::MAX); "capped")]
+    #[test_case("", None; "empty")]
+    #[test_case("K", None; "only suffix")]
+    #[test_case("1KB", None; "long suffix")]
+    fn test_parse_size(size: &str, expected: Option<usize>) {
+        assert_eq!(parse_size(size), expected);
+    }
+
+    #[test]
+    fn test_help() {
+        assert_eq!(parse_args(args(&["--help"])), Ok(Command::Help));
+        assert_eq!(parse_args(args(&["-r", "--help"])), Ok(Command::Help));
+        assert_eq!(
+            parse_args(args(&["-Vh"])),
+            Err("invalid option -- 'h'".to_string())
+        );
+    }
+
+    #[test_case(Config::default(), b"b10\nb9\n.a\n", b".a\nb9\nb10\n"; "sorts lines")]
+    #[test_case(Config::default(), b"b10\nb9", b"b9\nb10\n"; "missing final newline")]
+    #[test_case(Config::default(), b"", b""; "empty input")]
+    #[test_case(Config::default(), b"\n\n", b"\n\n"; "empty lines")]
+    #[test_case(
+        Config { reverse: true, ..Config::default() },
+        b"1.0~rc1\n1.0\n0.9\n",
+        b"1.0\n1.0~rc1\n0.9\n";
+        "reverse"
+    )]
+    #[test_case(
+        Config { unique: true, ..Config::default() },
+        b"b\na1\na01\na1\n",
+        b"a1\nb\n";
+        "unique"
+    )]
+    #[test_case(
+        Config { zero_terminated: true, ..Config::default() },
+        b"b10\nx\0b9\0",
+        b"b9\0b10\nx\0";
+        "zero terminated"
+    )]
+    #[test_case(
+        Config::default(),
+        b"caf\xe9 10\ncaf\xe9 2\n",
+        b"caf\xe9 2\ncaf\xe9 10\n";
+        "non utf-8 lines"
+    )]
+    #[test_case(Config::default(), b"b\r\na\r\n", b"a\r\nb\r\n"; "carriage returns are kept")]
+    #[test_case(
+        Config { separator: Some(b'\t'), keys: vec![Key::field(2)], ..Config::default() },
+        b"gcc\t10.2\nglibc\t2.31\nbash\t5.0\n",
+        b"glibc\t2.31\nbash\t5.0\ngcc\t10.2\n";
+        "key"
+    )]
+    #[test_case(
+        Config { keys: vec![Key::field(2)], ..Config::default() },
+        b"b 1.0\na 1.0\nc 0.9\n",
+        b"c 0.9\na 1.0\nb 1.0\n";
+        "last resort"
+    )]
+    #[test_case(
+        Config { reverse: true, keys: vec![Key::field(2)], ..Config::default() },
+        b"b 1.0\na 1.0\nc 0.9\n",
+        b"b 1.0\na 1.0\nc 0.9\n";
+        "reverse last resort"
+    )]
+    #[test_case(
+        Config { unique: true, keys: vec![Key::field(2)], ..Config::default() },
+        b"b 1.0\na 1.0\nc 0.9\n",
+        b"c 0.9\nb 1.0\n";
+        "unique keys"
+    )]
+    #[test_case(
+        Config { reverse: true, keys: vec!["2,2n".parse().unwrap()], ..Config::default() },
+        b"a 10\nb 9\nc 9\n",
+        b"c 9\nb 9\na 10\n";
+        "key options override reverse"
+    )]
+    #[test_case(
+        Config { buffer_size: Some(1), ..Config::default() },
+        b"b10\nb9\n.a\nb9\n",
+        b".a\nb9\nb9\nb10\n";
+        "small buffer"
+    )]
+    #[test_case(
+        Config { unique: true, buffer_size: Some(1), keys: vec![Key::field(2)], ..Config::default() },
+        b"b 1.0\na 1.0\nc 0.9\n",
+        b"c 0.9\nb 1.0\n";
+        "small buffer unique keys"
+    )]
+    #[test_case(
+        Config { merge: true, ..Config::default() },
+        b"b10\nb9\n",
+        b"b10\nb9\n";
+        "merge does not sort"
+    )]
+    fn test_run(config: Config, input: &[u8], expected: &[u8]) {
+        assert_eq!(sorted(config, input), expected);
+    }
+
+    #[test]
+    fn test_files_and_output() {
+        let dir = std::env::temp_dir().join(format!("vsort-cli-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("in.txt");
+        std::fs::write(&file, b"pkg-10\npkg-9\n").unwrap();
+
+        // The output file is also an input, like `sort -o FILE FILE`.
+        let config = Config {
+            output: Some(file.clone().into()),
+            files: vec![file.clone().into(), "-".into()],
+            ..Config::default()
+        };
+        assert_eq!(sorted(config, b"pkg-1\n"), b"");
+        assert_eq!(std::fs::read(&file).unwrap(), b"pkg-1\npkg-9\npkg-10\n");
+
+        // Merging into one of the inputs falls back to sorting, so no input is lost.
+        let other = dir.join("other.txt");
+        std::fs::write(&other, b"pkg-1.0\npkg-9\n").unwrap();
+        let config = Config {
+            unique: true,
+            merge: true,
+            output: Some(file.clone().into()),
+            files: vec![file.clone().into(), other.clone().into()],
+            ..Config::default()
+        };
+        assert_eq!(sorted(config, b""), b"");
+        assert_eq!(
+            std::fs::read(&file).unwrap(),
+            b"pkg-1\npkg-1.0\npkg-9\npkg-10\n"
+        );
+
+        let config = Config {
+            files: vec![dir.join("missing.txt").into()],
+            ..Config::default()
+        };
+        let err = run(&config, &b""[..], Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,358 @@
+//! Pluggable version orderings.
+//!
+//! `VersionComparator` is implemented by `Comparator`, by every `Mode` and by the combinators in
+//! this module, so code that sorts or merges versions can accept any of them.
+use core::cmp::Ordering;
+
+use crate::{apk, arch, compare_bytes, rpm, strverscmp_bytes, Comparator};
+#[cfg(feature = "alloc")]
+use crate::{deb, gomod, maven, pep440, semver};
+
+/// VersionComparator is a version ordering over byte strings. It lets code pass an ordering
+/// around without caring which one it is, and its combinators build new orderings from existing
+/// ones.
+/// # Examples
+/// ```
+/// use std::cmp::Ordering;
+/// use vsort::comparator::Mode;
+/// use vsort::VersionComparator;
+///
+/// fn main() {
+///     // Order "name version" lines by their version, newest first, then by their name.
+///     let comparator = Mode::Deb
+///         .by_key(|line| line.split(|&c| c == b' ').nth(1).unwrap_or_default())
+///         .reverse()
+///         .then_with(Mode::Gnu);
+///
+///     let mut lines = vec!["b 1.0~rc1", "a 1.0", "c 1.0", "d 0.9"];
+///     comparator.sort(&mut lines);
+///     assert_eq!(lines, vec!["a 1.0", "c 1.0", "b 1.0~rc1", "d 0.9"]);
+///     assert_eq!(comparator.compare(b"a 1.0", b"c 1.0"), Ordering::Less);
+/// }
+/// ```
+pub trait VersionComparator {
+    /// compare compares two byte strings, which do not need to be valid UTF-8.
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering;
+
+    /// reverse returns a comparator that orders in the opposite direction.
+    fn reverse(self) -> Reverse<Self>
+    where
+        Self: Sized,
+    {
+        Reverse(self)
+    }
+
+    /// then_with returns a comparator that uses `other` to order the strings this comparator
+    /// finds equal.
+    fn then_with<C: VersionComparator>(self, other: C) -> ThenWith<Self, C>
+    where
+        Self: Sized,
+    {
+        ThenWith {
+            first: self,
+            second: other,
+        }
+    }
+
+    /// by_key returns a comparator that compares the part of each string that `f` selects, e.g.
+    /// one field of a line.
+    fn by_key<F>(self, f: F) -> ByKey<Self, F>
+    where
+        Self: Sized,
+        F: Fn(&[u8]) -> &[u8],
+    {
+        ByKey {
+            comparator: self,
+            key: f,
+        }
+    }
+
+    /// sort will sort the given array in place using this comparator. The sort is stable.
+    #[cfg(feature = "alloc")]
+    fn sort<T: AsRef<[u8]>>(&self, arr: &mut [T])
+    where
+        Self: Sized,
+    {
+        arr.sort_by(|a, b| self.compare(a.as_ref(), b.as_ref()));
+    }
+
+    /// sort_unstable is like `sort`, but does not preserve the order of equal elements. It does
+    /// not allocate.
+    fn sort_unstable<T: AsRef<[u8]>>(&self, arr: &mut [T])
+    where
+        Self: Sized,
+    {
+        arr.sort_unstable_by(|a, b| self.compare(a.as_ref(), b.as_ref()));
+    }
+
+    /// sort_by_version_key will sort the given array in place using this comparator on the key
+    /// extracted from each element. Like `slice::sort_by_key`, the sort is stable and the key is
+    /// extracted on every comparison.
+    #[cfg(feature = "alloc")]
+    fn sort_by_version_key<T, K, F>(&self, arr: &mut [T], mut f: F)
+    where
+        Self: Sized,
+        K: AsRef<[u8]>,
+        F: FnMut(&T) -> K,
+    {
+        arr.sort_by(|a, b| self.compare(f(a).as_ref(), f(b).as_ref()));
+    }
+}
+
+impl<C: VersionComparator + ?Sized> VersionComparator for &C {
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        (**self).compare(a, b)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<C: VersionComparator + ?Sized> VersionComparator for alloc::boxed::Box<C> {
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        (**self).compare(a, b)
+    }
+}
+
+impl VersionComparator for Comparator {
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        self.compare_bytes(a, b)
+    }
+}
+
+/// Mode selects one of the version orderings of this crate.
+///
+/// The package version orderings compare strings. Byte strings that are not valid UTF-8 sort
+/// after all valid strings and are ordered with GNU version sort.
+/// # Examples
+/// ```
+/// use vsort::comparator::Mode;
+/// use vsort::VersionComparator;
+///
+/// fn main() {
+///     let mut versions = vec!["1.0", "1.0~rc1", "1.0a"];
+///     Mode::Gnu.sort(&mut versions);
+///     assert_eq!(versions, vec!["1.0~rc1", "1.0", "1.0a"]);
+///
+///     Mode::Arch.sort(&mut versions);
+///     assert_eq!(versions, vec!["1.0a", "1.0", "1.0~rc1"]);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Mode {
+    /// GNU version sort, like `compare_bytes` and gnulib's `filevercmp`.
+    #[default]
+    Gnu,
+    /// glibc's `strverscmp`, like `strverscmp_bytes`.
+    Strverscmp,
+    /// Alpine Linux versions, like `apk::compare`.
+    Apk,
+    /// Arch Linux versions, like `arch::compare`.
+    Arch,
+    /// Debian versions, like `deb::compare`.
+    #[cfg(feature = "alloc")]
+    Deb,
+    /// Go module versions, like `gomod::compare`.
+    #[cfg(feature = "alloc")]
+    GoMod,
+    /// Maven artifact versions, like `maven::compare`.
+    #[cfg(feature = "alloc")]
+    Maven,
+    /// Python package versions, like `pep440::compare`.
+    #[cfg(feature = "alloc")]
+    Pep440,
+    /// RPM versions, like `rpm::compare`.
+    Rpm,
+    /// Semantic versions, like `semver::compare`.
+    #[cfg(feature = "alloc")]
+    SemVer,
+}
+
+impl VersionComparator for Mode {
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        match self {
+            Mode::Gnu => compare_bytes(a, b),
+            Mode::Strverscmp => strverscmp_bytes(a, b),
+            Mode::Apk => compare_utf8(a, b, apk::compare),
+            Mode::Arch => compare_utf8(a, b, arch::compare),
+            #[cfg(feature = "alloc")]
+            Mode::Deb => compare_utf8(a, b, deb::compare),
+            #[cfg(feature = "alloc")]
+            Mode::GoMod => compare_utf8(a, b, gomod::compare),
+            #[cfg(feature = "alloc")]
+            Mode::Maven => compare_utf8(a, b, maven::compare),
+            #[cfg(feature = "alloc")]
+            Mode::Pep440 => compare_utf8(a, b, pep440::compare),
+            Mode::Rpm => compare_utf8(a, b, rpm::compare),
+            #[cfg(feature = "alloc")]
+            Mode::SemVer => compare_utf8(a, b, semver::compare),
+        }
+    }
+}
+
+// compare_utf8 compares two byte strings with a string ordering. Strings that are not valid UTF-8
+// sort last and are ordered with GNU version sort.
+fn compare_utf8(a: &[u8], b: &[u8], compare: fn(&str, &str) -> Ordering) -> Ordering {
+    match (core::str::from_utf8(a), core::str::from_utf8(b)) {
+        (Ok(a), Ok(b)) => compare(a, b),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => compare_bytes(a, b),
+    }
+}
+
+/// from_fn returns a comparator that calls the given function, e.g. `compare_bytes` or a closure.
+/// # Examples
+/// ```
+/// use std::cmp::Ordering;
+/// use vsort::comparator::from_fn;
+/// use vsort::{compare_bytes, VersionComparator};
+///
+/// fn main() {
+///     let by_length = from_fn(|a: &[u8], b: &[u8]| a.len().cmp(&b.len()));
+///     let comparator = by_length.then_with(from_fn(compare_bytes));
+///     assert_eq!(comparator.compare(b"b10", b"b9"), Ordering::Greater);
+///     assert_eq!(comparator.compare(b"b10", b"b11"), Ordering::Less);
+/// }
+/// ```
+pub fn from_fn<F: Fn(&[u8], &[u8]) -> Ordering>(f: F) -> FromFn<F> {
+    FromFn(f)
+}
+
+/// FromFn is the comparator returned by `from_fn`.
+#[derive(Clone, Copy, Debug)]
+pub struct FromFn<F>(F);
+
+impl<F: Fn(&[u8], &[u8]) -> Ordering> VersionComparator for FromFn<F> {
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        (self.0)(a, b)
+    }
+}
+
+/// Reverse is the comparator returned by `VersionComparator::reverse`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Reverse<C>(C);
+
+impl<C: VersionComparator> VersionComparator for Reverse<C> {
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        self.0.compare(a, b).reverse()
+    }
+}
+
+/// ThenWith is the comparator returned by `VersionComparator::then_with`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ThenWith<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A: VersionComparator, B: VersionComparator> VersionComparator for ThenWith<A, B> {
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        self.first
+            .compare(a, b)
+            .then_with(|| self.second.compare(a, b))
+    }
+}
+
+/// ByKey is the comparator returned by `VersionComparator::by_key`.
+#[derive(Clone, Copy, Debug)]
+pub struct ByKey<C, F> {
+    comparator: C,
+    key: F,
+}
+
+impl<C, F> VersionComparator for ByKey<C, F>
+where
+    C: VersionComparator,
+    F: Fn(&[u8]) -> &[u8],
+{
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        self.comparator.compare((self.key)(a), (self.key)(b))
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+    use crate::test::GNULIB_SORTED;
+    use crate::{compare, VersionSortOptions};
+
+    #[test]
+    fn test_gnu_matches_compare() {
+        for a in GNULIB_SORTED {
+            for b in GNULIB_SORTED {
+                assert_eq!(Mode::Gnu.compare(a.as_bytes(), b.as_bytes()), compare(a, b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_comparator_matches_options() {
+        let comparator = VersionSortOptions::new().reverse(true).build();
+        let mut list = GNULIB_SORTED.to_vec();
+        VersionComparator::sort(&comparator, &mut list);
+        list.reverse();
+        assert_eq!(list, GNULIB_SORTED);
+    }
+
+    #[test_case(Mode::Strverscmp, "010", "09", Ordering::Less)]
+    #[test_case(Mode::Apk, "1.0_rc1", "1.0", Ordering::Less)]
+    #[test_case(Mode::Arch, "1.0rc", "1.0", Ordering::Less)]
+    #[test_case(Mode::Deb, "1.0~rc1", "1.0", Ordering::Less)]
+    #[test_case(Mode::GoMod, "v1.2", "v1.2.0", Ordering::Equal)]
+    #[test_case(Mode::Maven, "1.0-SNAPSHOT", "1.0", Ordering::Less)]
+    #[test_case(Mode::Pep440, "1.0.dev1", "1.0a1", Ordering::Less)]
+    #[test_case(Mode::Rpm, "1.0^git1", "1.0", Ordering::Greater)]
+    #[test_case(Mode::SemVer, "1.0.0-alpha.10", "1.0.0-alpha.9", Ordering::Greater)]
+    fn test_modes(mode: Mode, a: &str, b: &str, expected: Ordering) {
+        assert_eq!(mode.compare(a.as_bytes(), b.as_bytes()), expected);
+        assert_eq!(mode.compare(b.as_bytes(), a.as_bytes()), expected.reverse());
+    }
+
+    #[test]
+    fn test_invalid_utf8_sorts_last() {
+        let mut list: Vec<&[u8]> = vec![b"1.0\xff2", b"2.0", b"1.0\xff10", b"1.0"];
+        Mode::Rpm.sort(&mut list);
+        assert_eq!(list, vec![&b"1.0"[..], b"2.0", b"1.0\xff2", b"1.0\xff10"]);
+    }
+
+    #[test]
+    fn test_reverse() {
+        let mut list = GNULIB_SORTED.to_vec();
+        Mode::Gnu.reverse().sort(&mut list);
+        list.reverse();
+        assert_eq!(list, GNULIB_SORTED);
+    }
+
+    #[test]
+    fn test_then_with() {
+        let comparator = VersionSortOptions::new()
+            .ignore_case(true)
+            .byte_tie_break(false)
+            .build()
+            .then_with(Mode::Gnu.reverse());
+        let mut list = vec!["A-1", "b-2", "a-1", "B-2"];
+        comparator.sort(&mut list);
+        assert_eq!(list, vec!["a-1", "A-1", "b-2", "B-2"]);
+    }
+
+    #[test]
+    fn test_by_key() {
+        let comparator = Mode::Gnu.by_key(|s| s.rsplit(|&c| c == b'/').next().unwrap());
+        let mut list = vec!["b/pkg-10", "a/pkg-9", "c/pkg-1"];
+        comparator.sort(&mut list);
+        assert_eq!(list, vec!["c/pkg-1", "a/pkg-9", "b/pkg-10"]);
+    }
+
+    #[test]
+    fn test_dyn_comparator() {
+        let comparators: Vec<Box<dyn VersionComparator>> =
+            vec![Box::new(Mode::Gnu), Box::new(Mode::Gnu.reverse())];
+        let mut list = vec!["a10", "a9"];
+        comparators[0].sort_unstable(&mut list);
+        assert_eq!(list, vec!["a9", "a10"]);
+        comparators[1].sort_unstable(&mut list);
+        assert_eq!(list, vec!["a10", "a9"]);
+    }
+}
@@ -0,0 +1,347 @@
+//! Debian package version ordering, as implemented by `dpkg --compare-versions`.
+//!
+//! A Debian version has the form `[epoch:]upstream_version[-debian_revision]`. Versions are
+//! ordered by their epoch first, then by their upstream version and finally by their Debian
+//! revision.
+//! https://www.debian.org/doc/debian-policy/ch-controlfields.html#version
+//!
+//! The upstream version and revision are compared with dpkg's `verrevcmp`, the same way GNU
+//! version sort compares names. They are split into alternating runs of non-digits and digits.
+//! Non-digit runs are compared character by character: the tilde sorts before everything, even
+//! the end of the string, and letters sort before all other characters. Digit runs are compared
+//! by their numeric value, so `1.10` is newer than `1.9`.
+use core::cmp::Ordering;
+use core::fmt;
+use core::str::FromStr;
+
+use alloc::string::{String, ToString};
+
+use crate::{compare_digit_seq, sequence_cmp};
+
+/// DebVersion is a parsed Debian package version.
+/// # Examples
+/// ```
+/// use vsort::deb::DebVersion;
+///
+/// fn main() {
+///     let version: DebVersion = "1:2.30-1ubuntu1".parse().unwrap();
+///     assert_eq!(version.epoch(), 1);
+///     assert_eq!(version.upstream(), "2.30");
+///     assert_eq!(version.revision(), "1ubuntu1");
+///
+///     let rc: DebVersion = "1:2.30~rc1-1".parse().unwrap();
+///     assert!(rc < version);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct DebVersion {
+    epoch: u32,
+    upstream: String,
+    revision: String,
+}
+
+impl DebVersion {
+    /// parse parses a version with the same validation as dpkg. Leading and trailing whitespace
+    /// is ignored.
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        let s = s.trim_matches(|c: char| c.is_ascii_whitespace());
+        if s.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        if s.contains(|c: char| c.is_ascii_whitespace()) {
+            return Err(ParseError::EmbeddedSpaces);
+        }
+
+        let (epoch, version) = match s.split_once(':') {
+            Some((epoch, version)) => {
+                let epoch = parse_epoch(epoch)?;
+                if version.is_empty() {
+                    return Err(ParseError::NothingAfterColon);
+                }
+                (epoch, version)
+            }
+            None => (0, s),
+        };
+        let (upstream, revision) = match version.rsplit_once('-') {
+            Some((_, "")) => return Err(ParseError::EmptyRevision),
+            Some((upstream, revision)) => (upstream, revision),
+            None => (version, ""),
+        };
+
+        match upstream.chars().next() {
+            None => return Err(ParseError::EmptyUpstream),
+            Some(c) if !c.is_ascii_digit() => return Err(ParseError::UpstreamNotStartingWithDigit),
+            Some(_) => {}
+        }
+        if let Some(c) = upstream
+            .chars()
+            .find(|&c| !(c.is_ascii_alphanumeric() || ".-+~:".contains(c)))
+        {
+            return Err(ParseError::InvalidUpstreamChar(c));
+        }
+        if let Some(c) = revision
+            .chars()
+            .find(|&c| !(c.is_ascii_alphanumeric() || ".+~".contains(c)))
+        {
+            return Err(ParseError::InvalidRevisionChar(c));
+        }
+
+        Ok(Self {
+            epoch,
+            upstream: upstream.to_string(),
+            revision: revision.to_string(),
+        })
+    }
+
+    /// epoch returns the epoch, which is 0 when the version does not have one.
+    pub fn epoch(&self) -> u32 {
+        self.epoch
+    }
+
+    /// upstream returns the upstream version.
+    pub fn upstream(&self) -> &str {
+        &self.upstream
+    }
+
+    /// revision returns the Debian revision, which is empty when the version does not have one.
+    pub fn revision(&self) -> &str {
+        &self.revision
+    }
+}
+
+fn parse_epoch(epoch: &str) -> Result<u32, ParseError> {
+    if epoch.is_empty() {
+        return Err(ParseError::EmptyEpoch);
+    }
+    let (negative, digits) = match epoch.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, epoch),
+    };
+    if digits.is_empty() || !digits.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(ParseError::InvalidEpoch);
+    }
+    if negative {
+        return Err(ParseError::NegativeEpoch);
+    }
+    // dpkg stores the epoch in an int.
+    digits
+        .parse::<u32>()
+        .ok()
+        .filter(|&epoch| epoch <= i32::MAX as u32)
+        .ok_or(ParseError::EpochTooBig)
+}
+
+impl FromStr for DebVersion {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for DebVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.epoch > 0 {
+            write!(f, "{}:", self.epoch)?;
+        }
+        f.write_str(&self.upstream)?;
+        if !self.revision.is_empty() {
+            write!(f, "-{}", self.revision)?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for DebVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.epoch
+            .cmp(&other.epoch)
+            .then_with(|| verrevcmp(&self.upstream, &other.upstream))
+            .then_with(|| verrevcmp(&self.revision, &other.revision))
+    }
+}
+
+impl PartialOrd for DebVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Like dpkg, versions that only differ in their spelling, e.g. "1.0" and "1.0-0", are equal.
+impl PartialEq for DebVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for DebVersion {}
+
+/// compare compares two Debian versions like `dpkg --compare-versions`.
+///
+/// Unlike `DebVersion::parse`, compare accepts any input: an epoch that is not a number is
+/// treated as part of the upstream version, and invalid characters are compared like any other.
+/// # Examples
+/// ```
+/// use std::cmp::Ordering;
+/// use vsort::deb;
+///
+/// fn main() {
+///     let mut versions = vec!["1.0-1", "1:0.9", "1.0~rc1-1", "1.0+b1", "1.0"];
+///     versions.sort_by(|a, b| deb::compare(a, b));
+///     assert_eq!(versions, vec!["1.0~rc1-1", "1.0", "1.0-1", "1.0+b1", "1:0.9"]);
+/// }
+/// ```
+pub fn compare(a: &str, b: &str) -> Ordering {
+    let (a_epoch, a_upstream, a_revision) = split(a);
+    let (b_epoch, b_upstream, b_revision) = split(b);
+    compare_digit_seq(a_epoch, b_epoch)
+        .then_with(|| verrevcmp(a_upstream, b_upstream))
+        .then_with(|| verrevcmp(a_revision, b_revision))
+}
+
+// split splits a version into its epoch digits, upstream version and revision without any
+// validation.
+fn split(s: &str) -> (&[u8], &str, &str) {
+    let s = s.trim_matches(|c: char| c.is_ascii_whitespace());
+    let (epoch, version) = match s.split_once(':') {
+        Some((epoch, version)) if epoch.bytes().all(|c| c.is_ascii_digit()) => {
+            (epoch.as_bytes(), version)
+        }
+        _ => (&b""[..], s),
+    };
+    let (upstream, revision) = version.rsplit_once('-').unwrap_or((version, ""));
+    (epoch, upstream, revision)
+}
+
+// verrevcmp is dpkg's comparison for upstream versions and revisions.
+fn verrevcmp(a: &str, b: &str) -> Ordering {
+    sequence_cmp(a.as_bytes(), b.as_bytes(), false)
+}
+
+/// ParseError describes why a string is not a valid Debian version. The messages match the
+/// ones dpkg reports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The version is empty.
+    Empty,
+    /// The version contains whitespace.
+    EmbeddedSpaces,
+    /// The version starts with a colon.
+    EmptyEpoch,
+    /// The epoch is not a number.
+    InvalidEpoch,
+    /// The epoch is negative.
+    NegativeEpoch,
+    /// The epoch does not fit in a 32-bit signed integer.
+    EpochTooBig,
+    /// The version ends with the colon that follows the epoch.
+    NothingAfterColon,
+    /// The version ends with the hyphen that precedes the revision.
+    EmptyRevision,
+    /// The upstream version is empty.
+    EmptyUpstream,
+    /// The upstream version does not start with a digit.
+    UpstreamNotStartingWithDigit,
+    /// The upstream version contains a character other than alphanumerics and `.+-~:`.
+    InvalidUpstreamChar(char),
+    /// The revision contains a character other than alphanumerics and `.+~`.
+    InvalidRevisionChar(char),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Empty => f.write_str("version string is empty"),
+            ParseError::EmbeddedSpaces => f.write_str("version string has embedded spaces"),
+            ParseError::EmptyEpoch => f.write_str("epoch in version is empty"),
+            ParseError::InvalidEpoch => f.write_str("epoch in version is not number"),
+            ParseError::NegativeEpoch => f.write_str("epoch in version is negative"),
+            ParseError::EpochTooBig => f.write_str("epoch in version is too big"),
+            ParseError::NothingAfterColon => f.write_str("nothing after colon in version number"),
+            ParseError::EmptyRevision => f.write_str("revision number is empty"),
+            ParseError::EmptyUpstream => f.write_str("upstream version is empty"),
+            ParseError::UpstreamNotStartingWithDigit => {
+                f.write_str("version number does not start with digit")
+            }
+            ParseError::InvalidUpstreamChar(c) => {
+                write!(f, "invalid character {:?} in version number", c)
+            }
+            ParseError::InvalidRevisionChar(c) => {
+                write!(f, "invalid character {:?} in revision number", c)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    // These tests are lifted from
+    // https://salsa.debian.org/dpkg-team/dpkg/-/blob/main/src/at/deb-version.at and
+    // https://salsa.debian.org/dpkg-team/dpkg/-/blob/main/lib/dpkg/t/t-version.c
+    #[test_case("1.0", "1.0", Ordering::Equal; "same version")]
+    #[test_case("0:0-0", "0:0-00", Ordering::Equal; "revision with leading zeros")]
+    #[test_case("1.0", "0:1.0", Ordering::Equal; "missing epoch is zero")]
+    #[test_case("1.0", "1.0-0", Ordering::Equal; "missing revision is zero")]
+    #[test_case("0:0-0", "1:0-0", Ordering::Less; "epoch")]
+    #[test_case("0:1a-0", "0:1b-0", Ordering::Less; "upstream")]
+    #[test_case("0:0-a", "0:0-b", Ordering::Less; "revision")]
+    #[test_case("1:0.9", "2.0", Ordering::Greater; "epoch beats upstream")]
+    #[test_case("1.0~rc1", "1.0", Ordering::Less; "tilde before end")]
+    #[test_case("1.0~~", "1.0~~a", Ordering::Less; "double tilde")]
+    #[test_case("1.0~~a", "1.0~", Ordering::Less; "tilde before letter")]
+    #[test_case("1.0", "1.0a", Ordering::Less; "end before letter")]
+    #[test_case("1.0a", "1.0+", Ordering::Less; "letters before other characters")]
+    #[test_case("1.0-1", "1.0+b1", Ordering::Less; "revision after binNMU")]
+    #[test_case("1.2.3", "1.2.10", Ordering::Less; "numbers")]
+    #[test_case("2:1.0-1", "10:0.1-1", Ordering::Less; "epochs are numbers")]
+    #[test_case("1.0-1-2", "1.0-1-10", Ordering::Less; "revision starts at last hyphen")]
+    fn test_compare(a: &str, b: &str, expected: Ordering) {
+        assert_eq!(compare(a, b), expected);
+        assert_eq!(compare(b, a), expected.reverse());
+        let a = DebVersion::parse(a).unwrap();
+        let b = DebVersion::parse(b).unwrap();
+        assert_eq!(a.cmp(&b), expected);
+    }
+
+    #[test_case("", ParseError::Empty; "empty")]
+    #[test_case("   ", ParseError::Empty; "only whitespace")]
+    #[test_case("1.0 1", ParseError::EmbeddedSpaces; "embedded spaces")]
+    #[test_case(":1.0", ParseError::EmptyEpoch; "empty epoch")]
+    #[test_case("a:1.0", ParseError::InvalidEpoch; "epoch not a number")]
+    #[test_case("-1:1.0", ParseError::NegativeEpoch; "negative epoch")]
+    #[test_case("9999999999:1.0", ParseError::EpochTooBig; "epoch too big")]
+    #[test_case("1:", ParseError::NothingAfterColon; "nothing after colon")]
+    #[test_case("1.0-", ParseError::EmptyRevision; "empty revision")]
+    #[test_case("-1", ParseError::EmptyUpstream; "empty upstream")]
+    #[test_case("a1.0", ParseError::UpstreamNotStartingWithDigit; "upstream starts with letter")]
+    #[test_case("1.0@", ParseError::InvalidUpstreamChar('@'); "invalid upstream char")]
+    #[test_case("1.0-1:1", ParseError::InvalidEpoch; "epoch ends at first colon")]
+    #[test_case("1:1.0-1:1", ParseError::InvalidRevisionChar(':'); "colon in revision")]
+    fn test_parse_errors(input: &str, expected: ParseError) {
+        assert_eq!(DebVersion::parse(input).unwrap_err(), expected);
+    }
+
+    #[test_case("1.0", 0, "1.0", ""; "upstream only")]
+    #[test_case(" 1.0-1 ", 0, "1.0", "1"; "surrounding whitespace")]
+    #[test_case("2:1.0-1-2", 2, "1.0-1", "2"; "hyphen in upstream")]
+    #[test_case("1:2:3", 1, "2:3", ""; "colon in upstream")]
+    fn test_parse(input: &str, epoch: u32, upstream: &str, revision: &str) {
+        let version = DebVersion::parse(input).unwrap();
+        assert_eq!(version.epoch(), epoch);
+        assert_eq!(version.upstream(), upstream);
+        assert_eq!(version.revision(), revision);
+    }
+
+    #[test_case("0:1.0-0", "1.0-0"; "zero epoch is dropped")]
+    #[test_case("3:1.0~rc1", "3:1.0~rc1"; "epoch without revision")]
+    fn test_display(input: &str, expected: &str) {
+        assert_eq!(DebVersion::parse(input).unwrap().to_string(), expected);
+    }
+}
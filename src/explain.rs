@@ -0,0 +1,317 @@
+use core::cmp::Ordering;
+use core::fmt;
+
+use crate::{
+    compare, compare_digit_seq, compare_non_digit_seq, digit_seq, non_digit_seq, split_ext, Token,
+};
+
+/// Rule is one of the GNU version sort rules, in the order they are applied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Rule {
+    /// Identical means the two strings are the same.
+    Identical,
+    /// SpecialName means one of the strings is "", "." or "..", which sort before all other
+    /// strings in that order.
+    SpecialName,
+    /// Hidden means exactly one of the strings starts with a period. Hidden files sort before
+    /// other names.
+    Hidden,
+    /// Stem means the names differ without their file extensions.
+    Stem,
+    /// FullName means the names only differ in their file extensions.
+    FullName,
+    /// ByteTieBreak means the names are equal as versions, e.g. "a1" and "a01", and their plain
+    /// bytes decide.
+    ByteTieBreak,
+}
+
+/// Difference is the first segment at which two names differ. A segment is a run of non-digits
+/// followed by a run of digits, and either run may be empty.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Difference<'a> {
+    /// index is the position of the segment, counting from zero.
+    pub index: usize,
+    /// a is the run of the first name that decided the order, with its byte span in that name.
+    pub a: Token<'a>,
+    /// b is the run of the second name that decided the order, with its byte span in that name.
+    pub b: Token<'a>,
+}
+
+/// Explanation describes why `compare` orders two strings the way it does.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Explanation<'a> {
+    a: &'a str,
+    b: &'a str,
+    ordering: Ordering,
+    rule: Rule,
+    difference: Option<Difference<'a>>,
+}
+
+impl<'a> Explanation<'a> {
+    /// ordering returns the result of `compare` for the two strings.
+    pub fn ordering(&self) -> Ordering {
+        self.ordering
+    }
+
+    /// rule returns the rule that decided the order.
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    /// difference returns the segment that decided the order. It is only present for the
+    /// `Stem`, `FullName` and `ByteTieBreak` rules.
+    pub fn difference(&self) -> Option<&Difference<'a>> {
+        self.difference.as_ref()
+    }
+}
+
+impl fmt::Display for Explanation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self.ordering {
+            Ordering::Less => "<",
+            Ordering::Equal => "=",
+            Ordering::Greater => ">",
+        };
+        write!(f, "{:?} {} {:?}: ", self.a, symbol, self.b)?;
+        match self.rule {
+            Rule::Identical => f.write_str("the strings are identical")?,
+            Rule::SpecialName => {
+                f.write_str("\"\", \".\" and \"..\" sort before all other names")?
+            }
+            Rule::Hidden => f.write_str("hidden files sort before other names")?,
+            Rule::Stem => f.write_str("the names differ without their extensions")?,
+            Rule::FullName => f.write_str("the names only differ in their extensions")?,
+            Rule::ByteTieBreak => {
+                f.write_str("the names are equal as versions, so bytes decide")?
+            }
+        }
+        if let Some(difference) = &self.difference {
+            write!(
+                f,
+                " at segment {}, {} vs {}",
+                difference.index,
+                DisplayToken(&difference.a),
+                DisplayToken(&difference.b)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+struct DisplayToken<'t, 'a>(&'t Token<'a>);
+
+impl fmt::Display for DisplayToken<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.0.is_digit() {
+            "digits"
+        } else {
+            "non-digits"
+        };
+        let span = self.0.span();
+        write!(
+            f,
+            "{} {:?} at {}..{}",
+            kind,
+            self.0.as_str(),
+            span.start,
+            span.end
+        )
+    }
+}
+
+/// explain reports which GNU version sort rule decides the order of two strings, and for names
+/// that are compared segment by segment, the first segment that differs. The ordering always
+/// matches `compare`.
+/// # Examples
+/// ```
+/// use std::cmp::Ordering;
+/// use vsort::{explain, Rule};
+///
+/// fn main() {
+///     let explanation = explain("gcc-10.fc9.tar.gz", "gcc-10.8.12.tar.gz");
+///     assert_eq!(explanation.ordering(), Ordering::Less);
+///     assert_eq!(explanation.rule(), Rule::Stem);
+///     assert_eq!(
+///         explanation.to_string(),
+///         r#""gcc-10.fc9.tar.gz" < "gcc-10.8.12.tar.gz": the names differ without their extensions at segment 1, non-digits "" at 6..6 vs non-digits "." at 6..7"#
+///     );
+/// }
+/// ```
+pub fn explain<'a>(a: &'a str, b: &'a str) -> Explanation<'a> {
+    let ordering = compare(a, b);
+    let explanation = |rule, difference| Explanation {
+        a,
+        b,
+        ordering,
+        rule,
+        difference,
+    };
+    if a == b {
+        return explanation(Rule::Identical, None);
+    }
+    if [a, b].iter().any(|s| matches!(*s, "" | "." | "..")) {
+        return explanation(Rule::SpecialName, None);
+    }
+    let offset = match (a.starts_with('.'), b.starts_with('.')) {
+        (true, true) => 1,
+        (false, false) => 0,
+        _ => return explanation(Rule::Hidden, None),
+    };
+    let (a_body, b_body) = (&a[offset..], &b[offset..]);
+
+    // Extensions only start at an ASCII period, so the stems end at character boundaries.
+    let a_stem = &a_body[..split_ext(a_body.as_bytes()).0.len()];
+    let b_stem = &b_body[..split_ext(b_body.as_bytes()).0.len()];
+    if let Some(difference) = first_difference(a_stem, b_stem, offset, false) {
+        return explanation(Rule::Stem, Some(difference));
+    }
+    if let Some(difference) = first_difference(a_body, b_body, offset, false) {
+        return explanation(Rule::FullName, Some(difference));
+    }
+    let difference = first_difference(a_body, b_body, offset, true);
+    explanation(Rule::ByteTieBreak, difference)
+}
+
+// first_difference walks the segments of a and b like sequence_cmp and returns the first run that
+// orders differently, or with by_bytes, the first run whose bytes differ. The spans are shifted
+// by offset so that they index into the full names.
+fn first_difference<'a>(
+    a: &'a str,
+    b: &'a str,
+    offset: usize,
+    by_bytes: bool,
+) -> Option<Difference<'a>> {
+    let differs = |a: &str, b: &str, digits: bool| {
+        if by_bytes {
+            a != b
+        } else if digits {
+            compare_digit_seq(a.as_bytes(), b.as_bytes()) != Ordering::Equal
+        } else {
+            compare_non_digit_seq(a.as_bytes(), b.as_bytes(), false) != Ordering::Equal
+        }
+    };
+    let (mut a_start, mut b_start) = (0, 0);
+    let mut index = 0;
+    while a_start < a.len() || b_start < b.len() {
+        let a_non_digit = non_digit_seq(&a.as_bytes()[a_start..]).0.len();
+        let b_non_digit = non_digit_seq(&b.as_bytes()[b_start..]).0.len();
+        let a_digit = digit_seq(&a.as_bytes()[a_start + a_non_digit..]).0.len();
+        let b_digit = digit_seq(&b.as_bytes()[b_start + b_non_digit..]).0.len();
+
+        let runs = [
+            (false, a_start, a_non_digit, b_start, b_non_digit),
+            (
+                true,
+                a_start + a_non_digit,
+                a_digit,
+                b_start + b_non_digit,
+                b_digit,
+            ),
+        ];
+        for (digits, a_run, a_len, b_run, b_len) in runs {
+            let a_text = &a[a_run..a_run + a_len];
+            let b_text = &b[b_run..b_run + b_len];
+            if differs(a_text, b_text, digits) {
+                return Some(Difference {
+                    index,
+                    a: token(a_text, offset + a_run, digits),
+                    b: token(b_text, offset + b_run, digits),
+                });
+            }
+        }
+
+        a_start += a_non_digit + a_digit;
+        b_start += b_non_digit + b_digit;
+        index += 1;
+    }
+    None
+}
+
+fn token(text: &str, start: usize, digits: bool) -> Token<'_> {
+    let span = start..start + text.len();
+    if digits {
+        Token::Digit { text, span }
+    } else {
+        Token::NonDigit { text, span }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+    use crate::test::GNULIB_SORTED;
+
+    #[test]
+    fn test_ordering_matches_compare() {
+        for a in GNULIB_SORTED {
+            for b in GNULIB_SORTED {
+                assert_eq!(explain(a, b).ordering(), compare(a, b), "{:?} {:?}", a, b);
+            }
+        }
+    }
+
+    #[test_case("a", "a", Rule::Identical; "identical")]
+    #[test_case("", "a", Rule::SpecialName; "empty")]
+    #[test_case("..", ".a", Rule::SpecialName; "dot dot")]
+    #[test_case(".b", "a", Rule::Hidden; "hidden")]
+    #[test_case(".a2", ".a10", Rule::Stem; "both hidden")]
+    #[test_case("a.tar.gz", "a.tar.bz2", Rule::FullName; "extension")]
+    #[test_case("a01", "a1", Rule::ByteTieBreak; "leading zeros")]
+    fn test_rule(a: &str, b: &str, rule: Rule) {
+        assert_eq!(explain(a, b).rule(), rule);
+        assert_eq!(explain(b, a).rule(), rule);
+    }
+
+    #[test_case(
+        ".a2", ".a10",
+        Difference {
+            index: 0,
+            a: Token::Digit { text: "2", span: 2..3 },
+            b: Token::Digit { text: "10", span: 2..4 },
+        };
+        "spans include the leading period"
+    )]
+    #[test_case(
+        "1.0~rc1", "1.0",
+        Difference {
+            index: 2,
+            a: Token::NonDigit { text: "~rc", span: 3..6 },
+            b: Token::NonDigit { text: "", span: 3..3 },
+        };
+        "missing segment"
+    )]
+    #[test_case(
+        "a.tar.gz", "a.tar.bz2",
+        Difference {
+            index: 0,
+            a: Token::NonDigit { text: "a.tar.gz", span: 0..8 },
+            b: Token::NonDigit { text: "a.tar.bz", span: 0..8 },
+        };
+        "extension"
+    )]
+    #[test_case(
+        "x1-a01", "x1-a1",
+        Difference {
+            index: 1,
+            a: Token::Digit { text: "01", span: 4..6 },
+            b: Token::Digit { text: "1", span: 4..5 },
+        };
+        "byte tie break"
+    )]
+    fn test_difference(a: &str, b: &str, expected: Difference) {
+        assert_eq!(explain(a, b).difference(), Some(&expected));
+    }
+
+    #[test_case("", ".", r#""" < ".": "", "." and ".." sort before all other names"#)]
+    #[test_case("b", ".a", r#""b" > ".a": hidden files sort before other names"#)]
+    #[test_case(
+        "a1", "a01",
+        r#""a1" > "a01": the names are equal as versions, so bytes decide at segment 0, digits "1" at 1..2 vs digits "01" at 1..3"#
+    )]
+    fn test_display(a: &str, b: &str, expected: &str) {
+        assert_eq!(explain(a, b).to_string(), expected);
+    }
+}
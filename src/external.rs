@@ -0,0 +1,568 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::mem;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+use crate::merge::MergeHeap;
+use crate::{Comparator, VersionComparator};
+
+// MERGE_WIDTH is the largest number of runs that are merged at once. More runs are merged in
+// several passes so that the number of open files stays small.
+const MERGE_WIDTH: usize = 16;
+
+/// ExternalSorter sorts lines that do not fit in memory. It reads lines until they take up the
+/// memory budget, sorts them and writes them to a temporary file, called a run. Once the input is
+/// exhausted, it merges the runs into the output. Input that fits in the budget is sorted in
+/// memory without temporary files.
+///
+/// The budget bounds the memory allocated for the lines and their bookkeeping. On top of it, the
+/// sorter holds a copy of the longest line and buffers for reading and writing.
+///
+/// Lines are byte strings that end with a delimiter, a newline by default. Like GNU sort, a final
+/// line without a delimiter gets one in the output. The sort is stable.
+/// # Examples
+/// ```
+/// use vsort::ExternalSorter;
+///
+/// fn main() -> std::io::Result<()> {
+///     let input = "pkg-10\npkg-9\npkg-1.0~rc1\npkg-1.0\n";
+///     let mut output = Vec::new();
+///
+///     // A budget of 64 bytes spills every couple of lines to a temporary file.
+///     ExternalSorter::new(64).sort(input.as_bytes(), &mut output)?;
+///     assert_eq!(output, b"pkg-1.0~rc1\npkg-1.0\npkg-9\npkg-10\n");
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ExternalSorter<C = Comparator> {
+    budget: usize,
+    temp_dir: Option<PathBuf>,
+    delimiter: u8,
+    unique: bool,
+    comparator: C,
+}
+
+impl ExternalSorter {
+    /// new returns a sorter that orders lines with GNU version sort and keeps about `budget`
+    /// bytes of lines in memory at a time.
+    pub fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            temp_dir: None,
+            delimiter: b'\n',
+            unique: false,
+            comparator: Comparator::default(),
+        }
+    }
+}
+
+impl<C: VersionComparator> ExternalSorter<C> {
+    /// comparator sets the order of the lines.
+    pub fn comparator<D: VersionComparator>(self, comparator: D) -> ExternalSorter<D> {
+        ExternalSorter {
+            budget: self.budget,
+            temp_dir: self.temp_dir,
+            delimiter: self.delimiter,
+            unique: self.unique,
+            comparator,
+        }
+    }
+
+    /// temp_dir sets the directory for the runs. Defaults to `std::env::temp_dir`.
+    pub fn temp_dir<P: Into<PathBuf>>(mut self, temp_dir: P) -> Self {
+        self.temp_dir = Some(temp_dir.into());
+        self
+    }
+
+    /// delimiter sets the byte that ends each line. Defaults to a newline.
+    pub fn delimiter(mut self, delimiter: u8) -> Self {
+        self.delimiter = delimiter;
+        self
+    }
+
+    /// unique writes only the first of the lines that compare as equal. Defaults to false.
+    pub fn unique(mut self, unique: bool) -> Self {
+        self.unique = unique;
+        self
+    }
+
+    /// sort writes the lines of the reader to the writer in sorted order. Nothing is written
+    /// before the reader has been read to the end.
+    pub fn sort<R: Read, W: Write>(&self, reader: R, writer: W) -> io::Result<()> {
+        self.sort_all([reader], writer)
+    }
+
+    /// merge writes the lines of readers that are each already sorted, like `sort -m`. Unlike
+    /// `sort`, it keeps only the next line of each reader in memory and writes lines as soon as
+    /// their place is known. Equal lines are written in the order of their readers.
+    /// # Examples
+    /// ```
+    /// use vsort::ExternalSorter;
+    ///
+    /// fn main() -> std::io::Result<()> {
+    ///     let mirror_a = "pkg-1.0~rc1\npkg-1.0\npkg-10\n";
+    ///     let mirror_b = "pkg-1.0\npkg-9\n";
+    ///     let mut output = Vec::new();
+    ///     ExternalSorter::new(0)
+    ///         .unique(true)
+    ///         .merge([mirror_a.as_bytes(), mirror_b.as_bytes()], &mut output)?;
+    ///     assert_eq!(output, b"pkg-1.0~rc1\npkg-1.0\npkg-9\npkg-10\n");
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn merge<I, R, W>(&self, readers: I, writer: W) -> io::Result<()>
+    where
+        I: IntoIterator<Item = R>,
+        R: Read,
+        W: Write,
+    {
+        let readers = readers.into_iter().map(BufReader::new).collect();
+        let mut output = Output::new(writer, self);
+        self.merge_into(readers, &mut output)?;
+        output.finish()
+    }
+
+    /// sort_all is like `sort`, but sorts the lines of several readers together. A final line
+    /// without a delimiter in one reader does not run into the first line of the next.
+    pub fn sort_all<I, R, W>(&self, readers: I, writer: W) -> io::Result<()>
+    where
+        I: IntoIterator<Item = R>,
+        R: Read,
+        W: Write,
+    {
+        let mut chunk = Chunk::default();
+        let mut runs = Vec::new();
+        for reader in readers {
+            let mut reader = BufReader::new(reader);
+            while chunk.read_line(&mut reader, self.delimiter)? {
+                if !chunk.fits(self.budget) && !chunk.is_empty() {
+                    runs.push(self.write_run(&mut chunk)?);
+                }
+                chunk.push_line(self.budget);
+            }
+        }
+
+        let mut output = Output::new(writer, self);
+        if runs.is_empty() {
+            chunk.sort(&self.comparator);
+            for line in chunk.lines() {
+                output.write_line(line)?;
+            }
+            return output.finish();
+        }
+        if !chunk.is_empty() {
+            runs.push(self.write_run(&mut chunk)?);
+        }
+        // Drop the lines before merging so that the budget is available for the merge buffers.
+        drop(chunk);
+        while runs.len() > MERGE_WIDTH {
+            runs = runs
+                .chunks(MERGE_WIDTH)
+                .map(|group| {
+                    let readers = run_readers(group)?;
+                    self.create_run(|output| self.merge_into(readers, output))
+                })
+                .collect::<io::Result<_>>()?;
+        }
+        self.merge_into(run_readers(&runs)?, &mut output)?;
+        output.finish()
+    }
+
+    fn run_dir(&self) -> PathBuf {
+        self.temp_dir.clone().unwrap_or_else(std::env::temp_dir)
+    }
+
+    // write_run sorts the lines of the chunk, writes them to a new run and empties the chunk.
+    fn write_run(&self, chunk: &mut Chunk) -> io::Result<TempFile> {
+        chunk.sort(&self.comparator);
+        let run =
+            self.create_run(|output| chunk.lines().try_for_each(|line| output.write_line(line)))?;
+        chunk.clear();
+        Ok(run)
+    }
+
+    // create_run writes a new run and closes it. Runs are only reopened to merge them, so no more
+    // than MERGE_WIDTH of them are open at a time, however many there are.
+    fn create_run<F>(&self, write: F) -> io::Result<TempFile>
+    where
+        F: FnOnce(&mut Output<File, C>) -> io::Result<()>,
+    {
+        let (run, file) = TempFile::new(self.run_dir())?;
+        let mut output = Output::new(file, self);
+        write(&mut output)?;
+        output.finish()?;
+        Ok(run)
+    }
+
+    // merge_into merges the sorted readers into the output. Equal lines are taken from the
+    // earlier reader first, which keeps the sort stable.
+    fn merge_into<R: BufRead, W: Write>(
+        &self,
+        mut readers: Vec<R>,
+        output: &mut Output<W, C>,
+    ) -> io::Result<()> {
+        let mut heap = MergeHeap::with_capacity(readers.len());
+        for (source, reader) in readers.iter_mut().enumerate() {
+            if let Some(line) = read_line(reader, self.delimiter)? {
+                heap.push(line, source, &self.comparator);
+            }
+        }
+        while let Some((line, source)) = heap.pop(&self.comparator) {
+            output.write_line(&line)?;
+            if let Some(next) = read_line(&mut readers[source], self.delimiter)? {
+                heap.push(next, source, &self.comparator);
+            }
+        }
+        Ok(())
+    }
+}
+
+// run_readers opens the runs for reading.
+fn run_readers(runs: &[TempFile]) -> io::Result<Vec<BufReader<File>>> {
+    runs.iter()
+        .map(|run| File::open(&run.path).map(BufReader::new))
+        .collect()
+}
+
+// Chunk holds the lines that are read into memory, stored back to back in one buffer. The
+// buffers grow like a Vec, but never past the budget, so the memory they allocate stays within it
+// unless a single line is larger. The next line is read into a separate buffer first.
+#[derive(Default)]
+struct Chunk {
+    data: Vec<u8>,
+    lines: Vec<Range<usize>>,
+    next: Vec<u8>,
+}
+
+const RANGE_SIZE: usize = mem::size_of::<Range<usize>>();
+
+impl Chunk {
+    // read_line reads the next line of the reader without its delimiter, to be added with
+    // push_line. It returns false at the end of the input.
+    fn read_line<R: BufRead>(&mut self, reader: &mut R, delimiter: u8) -> io::Result<bool> {
+        self.next.clear();
+        if reader.read_until(delimiter, &mut self.next)? == 0 {
+            return Ok(false);
+        }
+        if self.next.last() == Some(&delimiter) {
+            self.next.pop();
+        }
+        Ok(true)
+    }
+
+    // capacities returns the capacities of the buffers once the line that was just read is added.
+    // Each buffer doubles like a Vec, but only into the part of the budget that the other buffer
+    // has not allocated.
+    fn capacities(&self, budget: usize) -> (usize, usize) {
+        let data_limit = budget.saturating_sub(self.lines.capacity() * RANGE_SIZE);
+        let data = grown(&self.data, self.next.len(), data_limit);
+        let lines = grown(&self.lines, 1, budget.saturating_sub(data) / RANGE_SIZE);
+        (data, lines)
+    }
+
+    // fits reports whether the buffers stay within budget bytes once the line that was just read
+    // is added.
+    fn fits(&self, budget: usize) -> bool {
+        let (data, lines) = self.capacities(budget);
+        data.saturating_add(lines.saturating_mul(RANGE_SIZE)) <= budget
+    }
+
+    // push_line adds the line that was just read.
+    fn push_line(&mut self, budget: usize) {
+        let (data, lines) = self.capacities(budget);
+        self.data.reserve_exact(data - self.data.len());
+        self.lines.reserve_exact(lines - self.lines.len());
+        let start = self.data.len();
+        self.data.extend_from_slice(&self.next);
+        self.lines.push(start..self.data.len());
+    }
+
+    fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    fn sort<C: VersionComparator>(&mut self, comparator: &C) {
+        let data = &self.data;
+        self.lines
+            .sort_by(|a, b| comparator.compare(&data[a.clone()], &data[b.clone()]));
+    }
+
+    fn lines(&self) -> impl Iterator<Item = &[u8]> {
+        self.lines.iter().map(|range| &self.data[range.clone()])
+    }
+
+    // clear frees the buffers, so that the next chunk splits the budget between them afresh.
+    fn clear(&mut self) {
+        self.data = Vec::new();
+        self.lines = Vec::new();
+    }
+}
+
+// grown returns the capacity of vec after making room for additional elements. Like Vec, it
+// doubles the capacity, but never past limit elements unless the additional elements need more.
+fn grown<T>(vec: &Vec<T>, additional: usize, limit: usize) -> usize {
+    let needed = vec.len() + additional;
+    if needed <= vec.capacity() {
+        return vec.capacity();
+    }
+    vec.capacity().saturating_mul(2).min(limit).max(needed)
+}
+
+fn read_line<R: BufRead>(reader: &mut R, delimiter: u8) -> io::Result<Option<Vec<u8>>> {
+    let mut line = Vec::new();
+    if reader.read_until(delimiter, &mut line)? == 0 {
+        return Ok(None);
+    }
+    if line.last() == Some(&delimiter) {
+        line.pop();
+    }
+    Ok(Some(line))
+}
+
+// Output writes lines followed by the delimiter. With unique, it skips lines that are equal to
+// the previous one.
+struct Output<'c, W: Write, C> {
+    writer: BufWriter<W>,
+    delimiter: u8,
+    comparator: &'c C,
+    unique: bool,
+    previous: Option<Vec<u8>>,
+}
+
+impl<'c, W: Write, C: VersionComparator> Output<'c, W, C> {
+    fn new(writer: W, sorter: &'c ExternalSorter<C>) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            delimiter: sorter.delimiter,
+            comparator: &sorter.comparator,
+            unique: sorter.unique,
+            previous: None,
+        }
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        if self.unique {
+            if let Some(previous) = &mut self.previous {
+                if self.comparator.compare(previous, line).is_eq() {
+                    return Ok(());
+                }
+                previous.clear();
+                previous.extend_from_slice(line);
+            } else {
+                self.previous = Some(line.to_vec());
+            }
+        }
+        self.writer.write_all(line)?;
+        self.writer.write_all(&[self.delimiter])
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+// TempFile is the path of a file that is removed when it is dropped.
+struct TempFile {
+    path: PathBuf,
+}
+
+impl TempFile {
+    // new creates an empty file in dir and returns it opened for writing.
+    fn new(dir: PathBuf) -> io::Result<(Self, File)> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        loop {
+            let name = format!(
+                "vsort-{}-{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, AtomicOrdering::Relaxed)
+            );
+            let path = dir.join(name);
+            let mut options = OpenOptions::new();
+            options.write(true).create_new(true);
+            // Runs hold a copy of the input, so like mkstemp, only the owner may read them.
+            #[cfg(unix)]
+            std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+            match options.open(&path) {
+                Ok(file) => return Ok((Self { path }, file)),
+                // Another process with the same id left a file behind, so try the next name.
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(annotate(err, &dir)),
+            }
+        }
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        // The file only holds intermediate results, so failing to remove it is not an error.
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn annotate(err: io::Error, dir: &Path) -> io::Error {
+    io::Error::new(
+        err.kind(),
+        format!("cannot create temporary file in {}: {}", dir.display(), err),
+    )
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+    use crate::test::GNULIB_SORTED;
+    use crate::VersionSortOptions;
+
+    // test_dir returns an empty directory for the runs of one test.
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "vsort-external-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn sorted<C: VersionComparator>(sorter: &ExternalSorter<C>, inputs: &[&[u8]]) -> Vec<u8> {
+        let mut output = Vec::new();
+        sorter
+            .sort_all(inputs.iter().copied(), &mut output)
+            .unwrap();
+        output
+    }
+
+    #[test_case(usize::MAX; "in memory")]
+    #[test_case(256; "few runs")]
+    #[test_case(0; "several merge passes")]
+    fn test_sort(budget: usize) {
+        let dir = test_dir(&budget.to_string());
+        let lines = |list: Vec<&str>| list.iter().map(|s| format!("{}\n", s)).collect::<String>();
+        let input = lines(GNULIB_SORTED.iter().rev().copied().collect());
+        let sorter = ExternalSorter::new(budget).temp_dir(&dir);
+        assert_eq!(
+            sorted(&sorter, &[input.as_bytes()]),
+            lines(GNULIB_SORTED.to_vec()).into_bytes()
+        );
+        // The runs are removed once they are merged.
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    // A sort with more runs than the usual limit of 1024 open files only ever has a few of them
+    // open. The comparator counts the open runs while they are merged.
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_open_runs() {
+        let dir = test_dir("open-runs");
+        let open_runs = || {
+            fs::read_dir("/proc/self/fd")
+                .unwrap()
+                .filter_map(|fd| fs::read_link(fd.ok()?.path()).ok())
+                .filter(|path| path.starts_with(&dir))
+                .count()
+        };
+        let comparisons = std::cell::Cell::new(0);
+        let max_open_runs = std::cell::Cell::new(0);
+        let comparator = crate::comparator::from_fn(|a: &[u8], b: &[u8]| {
+            comparisons.set(comparisons.get() + 1);
+            if comparisons.get() % 64 == 0 {
+                max_open_runs.set(max_open_runs.get().max(open_runs()));
+            }
+            crate::compare_bytes(a, b)
+        });
+        let input: String = (0..1500).rev().map(|i| format!("{}\n", i)).collect();
+        let expected: String = (0..1500).map(|i| format!("{}\n", i)).collect();
+        let sorter = ExternalSorter::new(0).temp_dir(&dir).comparator(comparator);
+        assert_eq!(sorted(&sorter, &[input.as_bytes()]), expected.as_bytes());
+        assert!(max_open_runs.get() > 0);
+        assert!(
+            max_open_runs.get() <= MERGE_WIDTH + 1,
+            "{}",
+            max_open_runs.get()
+        );
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test_case(false, b"a01\na1\na001\nb\n"; "stable")]
+    #[test_case(true, b"a01\nb\n"; "unique")]
+    fn test_equal_lines(unique: bool, expected: &[u8]) {
+        let dir = test_dir(&format!("equal-{}", unique));
+        let comparator = VersionSortOptions::new().byte_tie_break(false).build();
+        let sorter = ExternalSorter::new(0)
+            .temp_dir(&dir)
+            .comparator(comparator)
+            .unique(unique);
+        assert_eq!(sorted(&sorter, &[b"b\na01\n", b"a1\na001\n"]), expected);
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test_case(b'\n', &[b"b10\nb9", b"b1"], b"b1\nb9\nb10\n"; "missing final delimiter")]
+    #[test_case(b'\0', &[b"b10\nx\0b9\0"], b"b9\0b10\nx\0"; "nul delimiter")]
+    #[test_case(b'\n', &[b"", b"\n\n"], b"\n\n"; "empty lines")]
+    fn test_delimiter(delimiter: u8, inputs: &[&[u8]], expected: &[u8]) {
+        let sorter = ExternalSorter::new(usize::MAX).delimiter(delimiter);
+        assert_eq!(sorted(&sorter, inputs), expected);
+    }
+
+    #[test_case(false, b"a\nb9\nb9\nb10\nc\n"; "duplicates are kept")]
+    #[test_case(true, b"a\nb9\nb10\nc\n"; "unique")]
+    fn test_merge(unique: bool, expected: &[u8]) {
+        let mut output = Vec::new();
+        ExternalSorter::new(0)
+            .unique(unique)
+            .merge([&b"b9\nb10\nc"[..], b"a\nb9\n"], &mut output)
+            .unwrap();
+        assert_eq!(output, expected);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = test_dir("permissions");
+        let (run, _file) = TempFile::new(dir.clone()).unwrap();
+        let mode = fs::metadata(&run.path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        drop(run);
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test_case(0; "no budget")]
+    #[test_case(100; "small budget")]
+    #[test_case(1000; "larger budget")]
+    fn test_chunk_stays_within_budget(budget: usize) {
+        let input: String = (0..500).map(|i| format!("line-{}\n", i)).collect();
+        let mut reader = input.as_bytes();
+        let mut chunk = Chunk::default();
+        let mut count = 0;
+        while chunk.read_line(&mut reader, b'\n').unwrap() {
+            if !chunk.fits(budget) && !chunk.is_empty() {
+                count += chunk.lines.len();
+                chunk.clear();
+            }
+            chunk.push_line(budget);
+            let used = chunk.data.len() + chunk.lines.len() * RANGE_SIZE;
+            let allocated = chunk.data.capacity() + chunk.lines.capacity() * RANGE_SIZE;
+            assert!(allocated <= budget.max(used), "{} > {}", allocated, budget);
+        }
+        assert_eq!(count + chunk.lines.len(), 500);
+    }
+
+    #[test]
+    fn test_missing_temp_dir() {
+        let dir = std::env::temp_dir().join("vsort-external-test-missing/runs");
+        let mut output = Vec::new();
+        let err = ExternalSorter::new(0)
+            .temp_dir(&dir)
+            .sort(&b"b\na\n"[..], &mut output)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(output.is_empty());
+    }
+}
@@ -0,0 +1,400 @@
+//! Go module version ordering, as implemented by `golang.org/x/mod/semver`.
+//!
+//! Go module versions are semantic versions with a required `v` prefix, e.g. `v1.2.3`. The
+//! shorthands `vMAJOR` and `vMAJOR.MINOR` are accepted and stand for `vMAJOR.0.0` and
+//! `vMAJOR.MINOR.0`. Build metadata such as `+incompatible` is ignored for precedence.
+//!
+//! Pseudo-versions like `v0.0.0-20230101120000-abcdef123456` refer to a commit that is not tagged.
+//! They are pre-releases of the version after the closest tag, so they sort between that tag and
+//! the next release.
+//! https://go.dev/ref/mod#versions
+use core::cmp::Ordering;
+
+use alloc::string::String;
+
+use crate::compare_digit_seq;
+use crate::semver::{compare_prerelease, is_numeric, validate_identifier};
+
+// Parts is a valid version split into its parts. The minor and patch numbers are "0" for the
+// shorthand forms.
+struct Parts<'a> {
+    major: &'a str,
+    minor: &'a str,
+    patch: &'a str,
+    // What the shorthand forms leave out: ".0.0" for vMAJOR and ".0" for vMAJOR.MINOR.
+    short: &'static str,
+    // The pre-release identifiers without the leading hyphen, empty for releases.
+    prerelease: &'a str,
+    // The build metadata including the leading plus sign, e.g. "+incompatible".
+    build: &'a str,
+}
+
+impl<'a> Parts<'a> {
+    // parse follows parse in golang.org/x/mod/semver.
+    fn parse(v: &'a str) -> Option<Self> {
+        let rest = v.strip_prefix('v')?;
+        let (major, rest) = parse_int(rest)?;
+        let (minor, rest) = match rest.strip_prefix('.') {
+            Some(rest) => parse_int(rest)?,
+            None if rest.is_empty() => return Some(Self::short(major, "0", ".0.0")),
+            None => return None,
+        };
+        let (patch, rest) = match rest.strip_prefix('.') {
+            Some(rest) => parse_int(rest)?,
+            None if rest.is_empty() => return Some(Self::short(major, minor, ".0")),
+            None => return None,
+        };
+
+        let (rest, build) = match rest.find('+') {
+            Some(i) => rest.split_at(i),
+            None => (rest, ""),
+        };
+        let prerelease = match rest.strip_prefix('-') {
+            Some(prerelease) => prerelease,
+            None if rest.is_empty() => "",
+            None => return None,
+        };
+        if !rest.is_empty() {
+            for identifier in prerelease.split('.') {
+                validate_identifier(identifier).ok()?;
+                if is_numeric(identifier) && identifier.len() > 1 && identifier.starts_with('0') {
+                    return None;
+                }
+            }
+        }
+        if let Some(build) = build.strip_prefix('+') {
+            for identifier in build.split('.') {
+                validate_identifier(identifier).ok()?;
+            }
+        }
+
+        Some(Self {
+            major,
+            minor,
+            patch,
+            short: "",
+            prerelease,
+            build,
+        })
+    }
+
+    fn short(major: &'a str, minor: &'a str, short: &'static str) -> Self {
+        Self {
+            major,
+            minor,
+            patch: "0",
+            short,
+            prerelease: "",
+            build: "",
+        }
+    }
+
+    fn compare(&self, other: &Self) -> Ordering {
+        compare_digit_seq(self.major.as_bytes(), other.major.as_bytes())
+            .then_with(|| compare_digit_seq(self.minor.as_bytes(), other.minor.as_bytes()))
+            .then_with(|| compare_digit_seq(self.patch.as_bytes(), other.patch.as_bytes()))
+            .then_with(|| compare_prerelease(self.prerelease, other.prerelease))
+    }
+}
+
+// parse_int splits off a number without leading zeros.
+fn parse_int(v: &str) -> Option<(&str, &str)> {
+    let len = v.bytes().take_while(|c| c.is_ascii_digit()).count();
+    if len == 0 || (len > 1 && v.starts_with('0')) {
+        return None;
+    }
+    Some(v.split_at(len))
+}
+
+/// is_valid reports whether the given string is a valid Go module version.
+/// # Examples
+/// ```
+/// use vsort::gomod;
+///
+/// fn main() {
+///     assert!(gomod::is_valid("v1.2.3+incompatible"));
+///     assert!(gomod::is_valid("v1.2"));
+///     assert!(!gomod::is_valid("1.2.3"));
+/// }
+/// ```
+pub fn is_valid(v: &str) -> bool {
+    Parts::parse(v).is_some()
+}
+
+/// canonical returns the canonical form of the given version: the shorthands are expanded and
+/// build metadata is removed. It returns None if the version is not valid.
+/// # Examples
+/// ```
+/// use vsort::gomod;
+///
+/// fn main() {
+///     assert_eq!(gomod::canonical("v1.2").as_deref(), Some("v1.2.0"));
+///     assert_eq!(gomod::canonical("v2.0.0+incompatible").as_deref(), Some("v2.0.0"));
+/// }
+/// ```
+pub fn canonical(v: &str) -> Option<String> {
+    let parts = Parts::parse(v)?;
+    let mut canonical = String::from(&v[..v.len() - parts.build.len()]);
+    canonical.push_str(parts.short);
+    Some(canonical)
+}
+
+/// compare compares two versions like `semver.Compare` in `golang.org/x/mod`.
+///
+/// Invalid versions sort before all valid versions and are equal to each other. Versions that
+/// only differ in their build metadata, e.g. `v2.0.0` and `v2.0.0+incompatible`, are equal too.
+/// Use `sort` to break these ties.
+/// # Examples
+/// ```
+/// use std::cmp::Ordering;
+/// use vsort::gomod;
+///
+/// fn main() {
+///     assert_eq!(gomod::compare("v1.2.3", "v1.10.0"), Ordering::Less);
+///     assert_eq!(gomod::compare("v1.2", "v1.2.0"), Ordering::Equal);
+///     assert_eq!(gomod::compare("bad", "v0.0.0"), Ordering::Less);
+/// }
+/// ```
+pub fn compare(a: &str, b: &str) -> Ordering {
+    match (Parts::parse(a), Parts::parse(b)) {
+        (Some(a), Some(b)) => a.compare(&b),
+        (Some(_), None) => Ordering::Greater,
+        (None, Some(_)) => Ordering::Less,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// sort sorts the given versions like `semver.Sort` in `golang.org/x/mod`: by `compare`, and by
+/// the strings themselves when `compare` finds them equal.
+/// # Examples
+/// ```
+/// use vsort::gomod;
+///
+/// fn main() {
+///     let mut versions = vec![
+///         "v2.0.0+incompatible",
+///         "v1.2.3",
+///         "v0.0.0-20230101120000-abcdef123456",
+///         "v1.2.4-0.20230101120000-abcdef123456",
+///         "v2.0.0",
+///     ];
+///     gomod::sort(&mut versions);
+///     assert_eq!(
+///         versions,
+///         vec![
+///             "v0.0.0-20230101120000-abcdef123456",
+///             "v1.2.3",
+///             "v1.2.4-0.20230101120000-abcdef123456",
+///             "v2.0.0",
+///             "v2.0.0+incompatible",
+///         ]
+///     );
+/// }
+/// ```
+pub fn sort<T: AsRef<str>>(arr: &mut [T]) {
+    arr.sort_by(|a, b| {
+        let (a, b) = (a.as_ref(), b.as_ref());
+        compare(a, b).then_with(|| a.cmp(b))
+    });
+}
+
+/// is_pseudo_version reports whether the given version is a pseudo-version, like
+/// `IsPseudoVersion` in `golang.org/x/mod/module`.
+/// # Examples
+/// ```
+/// use vsort::gomod;
+///
+/// fn main() {
+///     assert!(gomod::is_pseudo_version("v0.0.0-20230101120000-abcdef123456"));
+///     assert!(!gomod::is_pseudo_version("v1.0.0-rc.1"));
+/// }
+/// ```
+pub fn is_pseudo_version(v: &str) -> bool {
+    split_pseudo_version(v).is_some()
+}
+
+/// pseudo_version_time returns the commit timestamp of a pseudo-version in the form
+/// `yyyymmddhhmmss`, in UTC. It returns None if the version is not a pseudo-version.
+/// # Examples
+/// ```
+/// use vsort::gomod;
+///
+/// fn main() {
+///     let v = "v1.2.4-0.20230101120000-abcdef123456";
+///     assert_eq!(gomod::pseudo_version_time(v), Some("20230101120000"));
+/// }
+/// ```
+pub fn pseudo_version_time(v: &str) -> Option<&str> {
+    split_pseudo_version(v).map(|(timestamp, _)| timestamp)
+}
+
+/// pseudo_version_rev returns the revision identifier of a pseudo-version, which is usually an
+/// abbreviated commit hash. It returns None if the version is not a pseudo-version.
+/// # Examples
+/// ```
+/// use vsort::gomod;
+///
+/// fn main() {
+///     let v = "v1.2.4-0.20230101120000-abcdef123456";
+///     assert_eq!(gomod::pseudo_version_rev(v), Some("abcdef123456"));
+/// }
+/// ```
+pub fn pseudo_version_rev(v: &str) -> Option<&str> {
+    split_pseudo_version(v).map(|(_, rev)| rev)
+}
+
+// split_pseudo_version returns the timestamp and revision of a pseudo-version. A pseudo-version
+// has one of the forms
+//
+//   vX.0.0-yyyymmddhhmmss-abcdefabcdef
+//   vX.Y.Z-pre.0.yyyymmddhhmmss-abcdefabcdef
+//   vX.Y.(Z+1)-0.yyyymmddhhmmss-abcdefabcdef
+//
+// optionally followed by build metadata.
+fn split_pseudo_version(v: &str) -> Option<(&str, &str)> {
+    let parts = Parts::parse(v)?;
+    if !parts.short.is_empty() || v.matches('-').count() < 2 {
+        return None;
+    }
+    let (rest, rev) = v[..v.len() - parts.build.len()].rsplit_once('-')?;
+    if rev.is_empty() || !rev.bytes().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    let (prefix, timestamp) = rest.split_at(rest.len().checked_sub(14)?);
+    if !timestamp.bytes().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    // The prefix is everything up to the timestamp, e.g. "v1.2.3-pre.0.".
+    let core_len = 1 + parts.major.len() + 1 + parts.minor.len() + 1 + parts.patch.len();
+    let is_pseudo = match prefix.strip_suffix("0.") {
+        Some(base) => {
+            let after_core = &base[core_len.min(base.len())..];
+            after_core == "-" || (after_core.starts_with('-') && after_core.ends_with('.'))
+        }
+        None => prefix.len() == core_len + 1 && parts.minor == "0" && parts.patch == "0",
+    };
+    if !is_pseudo {
+        return None;
+    }
+    Some((timestamp, rev))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    // These tests are lifted from
+    // https://github.com/golang/mod/blob/master/semver/semver_test.go
+    // Each version is paired with its canonical form, which is empty for invalid versions. The
+    // versions are sorted, and versions with the same canonical form are equal.
+    const GO_TESTS: &[(&str, &str)] = &[
+        ("bad", ""),
+        ("v1-alpha.beta.gamma", ""),
+        ("v1-pre", ""),
+        ("v1+meta", ""),
+        ("v1-pre+meta", ""),
+        ("v1.2-pre", ""),
+        ("v1.2+meta", ""),
+        ("v1.2-pre+meta", ""),
+        ("v1.0.0-alpha", "v1.0.0-alpha"),
+        ("v1.0.0-alpha.1", "v1.0.0-alpha.1"),
+        ("v1.0.0-alpha.beta", "v1.0.0-alpha.beta"),
+        ("v1.0.0-beta", "v1.0.0-beta"),
+        ("v1.0.0-beta.2", "v1.0.0-beta.2"),
+        ("v1.0.0-beta.11", "v1.0.0-beta.11"),
+        ("v1.0.0-rc.1", "v1.0.0-rc.1"),
+        ("v1", "v1.0.0"),
+        ("v1.0", "v1.0.0"),
+        ("v1.0.0", "v1.0.0"),
+        ("v1.2", "v1.2.0"),
+        ("v1.2.0", "v1.2.0"),
+        ("v1.2.3-456", "v1.2.3-456"),
+        ("v1.2.3-456.789", "v1.2.3-456.789"),
+        ("v1.2.3-456-789", "v1.2.3-456-789"),
+        ("v1.2.3-456a", "v1.2.3-456a"),
+        ("v1.2.3-pre", "v1.2.3-pre"),
+        ("v1.2.3-pre+meta", "v1.2.3-pre"),
+        ("v1.2.3-pre.1", "v1.2.3-pre.1"),
+        ("v1.2.3-zzz", "v1.2.3-zzz"),
+        ("v1.2.3", "v1.2.3"),
+        ("v1.2.3+meta", "v1.2.3"),
+        ("v1.2.3+meta-pre", "v1.2.3"),
+        ("v1.2.3+meta-pre.sha.256a", "v1.2.3"),
+    ];
+
+    #[test]
+    fn test_is_valid_and_canonical() {
+        for &(v, want) in GO_TESTS {
+            assert_eq!(is_valid(v), !want.is_empty(), "is_valid({:?})", v);
+            assert_eq!(canonical(v).unwrap_or_default(), want, "canonical({:?})", v);
+        }
+    }
+
+    #[test]
+    fn test_compare() {
+        for (i, &(a, a_out)) in GO_TESTS.iter().enumerate() {
+            for (j, &(b, b_out)) in GO_TESTS.iter().enumerate() {
+                let want = if a_out == b_out {
+                    Ordering::Equal
+                } else {
+                    i.cmp(&j)
+                };
+                assert_eq!(compare(a, b), want, "compare({:?}, {:?})", a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut versions = vec!["v1.0.0+b", "v1.0.0+a", "v1", "bad", "v0.9.0", "also-bad"];
+        sort(&mut versions);
+        assert_eq!(
+            versions,
+            vec!["also-bad", "bad", "v0.9.0", "v1", "v1.0.0+a", "v1.0.0+b"]
+        );
+    }
+
+    // These versions are lifted from
+    // https://github.com/golang/mod/blob/master/module/pseudo_test.go
+    #[test_case("v0.0.0-20060102150405-hash")]
+    #[test_case("v1.0.0-20060102150405-hash")]
+    #[test_case("v2.0.0-20060102150405-hash")]
+    #[test_case("v0.0.1-0.20060102150405-hash")]
+    #[test_case("v1.2.4-0.20060102150405-hash")]
+    #[test_case("v1.2.100000000000000000-0.20060102150405-hash")]
+    #[test_case("v1.2.3-pre.0.20060102150405-hash")]
+    #[test_case("v1.3.0-pre.0.20060102150405-hash")]
+    #[test_case("v0.0.0--.0.20060102150405-hash")]
+    #[test_case("v1.0.1-0.20060102150405-hash+metadata")]
+    #[test_case("v2.0.1-0.20060102150405-hash+incompatible")]
+    #[test_case("v2.3.0-pre.0.20060102150405-hash+incompatible")]
+    fn test_pseudo_version(v: &str) {
+        assert!(is_pseudo_version(v));
+        assert_eq!(pseudo_version_time(v), Some("20060102150405"));
+        assert_eq!(pseudo_version_rev(v), Some("hash"));
+    }
+
+    #[test_case("v1.2.3"; "release")]
+    #[test_case("v1.2.3-pre"; "pre-release")]
+    #[test_case("v0.0.0-2006010215040-hash"; "short timestamp")]
+    #[test_case("v0.0.0-2006010215040a-hash"; "letter in timestamp")]
+    #[test_case("v1.2.3-20060102150405-hash"; "timestamp after non-zero version")]
+    #[test_case("v1.2.3-pre0.20060102150405-hash"; "no period before zero")]
+    #[test_case("v0.0.0-20060102150405-"; "empty revision")]
+    #[test_case("0.0.0-20060102150405-hash"; "missing v")]
+    fn test_not_pseudo_version(v: &str) {
+        assert!(!is_pseudo_version(v));
+        assert_eq!(pseudo_version_time(v), None);
+    }
+
+    #[test_case("v0.0.0-20230101120000-abcdef123456", "v0.0.1", Ordering::Less; "before next release")]
+    #[test_case("v1.2.3", "v1.2.4-0.20230101120000-abcdef123456", Ordering::Less; "after last tag")]
+    #[test_case("v1.2.4-0.20230101120000-abcdef123456", "v1.2.4-0.20230102120000-012345abcdef", Ordering::Less; "by timestamp")]
+    #[test_case("v2.0.0+incompatible", "v2.0.0", Ordering::Equal; "incompatible is build metadata")]
+    fn test_compare_pseudo_versions(a: &str, b: &str, expected: Ordering) {
+        assert_eq!(compare(a, b), expected);
+        assert_eq!(compare(b, a), expected.reverse());
+    }
+}
@@ -0,0 +1,541 @@
+use core::cmp::Ordering;
+use core::fmt;
+use core::str::FromStr;
+
+use alloc::vec::Vec;
+
+use crate::{compare_bytes_with, compare_digit_seq, VersionComparator, VersionSortOptions};
+
+// Keys compare like GNU sort's filevercmp, which leaves breaking ties to the last-resort
+// comparison of the whole lines.
+const VERSION_OPTIONS: VersionSortOptions = VersionSortOptions::new().byte_tie_break(false);
+
+/// KeyMode selects how the text of a key is compared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum KeyMode {
+    /// Version compares keys with GNU version sort, like `sort -V`.
+    Version,
+    /// Numeric compares keys by their leading decimal number, like `sort -n`. Leading blanks, a
+    /// minus sign and a fractional part are allowed, and a key without a number counts as zero.
+    Numeric,
+    /// Lexical compares the bytes of the keys, like `sort` in the C locale.
+    Lexical,
+}
+
+/// Key selects a part of a line, from a start position to an end position, and how to compare
+/// it. Fields and characters are numbered from 1, like in the `-k` option of GNU sort.
+/// # Examples
+/// ```
+/// use vsort::{Key, KeyMode};
+///
+/// fn main() {
+///     // The same as `sort -k2,2nr`.
+///     let key = Key::field(2).mode(KeyMode::Numeric).reverse(true);
+///     assert_eq!("2,2nr".parse(), Ok(key));
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Key {
+    // The fields and characters are stored zero-based. An end character of 0 means the end of
+    // the end field, and a missing end field means the end of the line.
+    start_field: usize,
+    start_char: usize,
+    end_field: Option<usize>,
+    end_char: usize,
+    skip_start_blanks: bool,
+    skip_end_blanks: bool,
+    mode: KeyMode,
+    reverse: bool,
+    // Whether the key sets none of its own options, so that it follows the options of the
+    // KeySpec.
+    inherits: bool,
+}
+
+impl Key {
+    /// new returns a key that starts at the given field and ends at the end of the line. It
+    /// compares versions.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the field is 0.
+    pub fn new(field: usize) -> Self {
+        assert!(field > 0, "field numbers start at 1");
+        Self {
+            start_field: field - 1,
+            start_char: 0,
+            end_field: None,
+            end_char: 0,
+            skip_start_blanks: false,
+            skip_end_blanks: false,
+            mode: KeyMode::Version,
+            reverse: false,
+            inherits: true,
+        }
+    }
+
+    /// field returns a key that spans exactly the given field, like `-k2,2`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the field is 0.
+    pub fn field(field: usize) -> Self {
+        Self::new(field).end(field, 0)
+    }
+
+    /// start_char makes the key start at the given character of its start field instead of at
+    /// its first character.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the character is 0.
+    pub fn start_char(mut self, start_char: usize) -> Self {
+        assert!(start_char > 0, "character offsets start at 1");
+        self.start_char = start_char - 1;
+        self
+    }
+
+    /// end makes the key end at the given character of the given field, inclusive. A character
+    /// of 0 means the last character of the field.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the field is 0.
+    pub fn end(mut self, field: usize, end_char: usize) -> Self {
+        assert!(field > 0, "field numbers start at 1");
+        self.end_field = Some(field - 1);
+        self.end_char = end_char;
+        self
+    }
+
+    /// ignore_leading_blanks skips the blanks at the start of the start and end fields before
+    /// counting characters, like the `b` option.
+    pub fn ignore_leading_blanks(mut self, ignore_leading_blanks: bool) -> Self {
+        self.skip_start_blanks = ignore_leading_blanks;
+        self.skip_end_blanks = ignore_leading_blanks;
+        self.inherits = false;
+        self
+    }
+
+    /// mode sets how the key is compared. Defaults to `KeyMode::Version`.
+    pub fn mode(mut self, mode: KeyMode) -> Self {
+        self.mode = mode;
+        self.inherits = false;
+        self
+    }
+
+    /// reverse reverses the result of comparing this key. Defaults to false.
+    pub fn reverse(mut self, reverse: bool) -> Self {
+        self.reverse = reverse;
+        self.inherits = false;
+        self
+    }
+
+    // text returns the part of the line that the key selects, following begfield and limfield in
+    // GNU sort.
+    fn text<'a>(&self, line: &'a [u8], separator: Option<u8>) -> &'a [u8] {
+        let start = self.text_start(line, separator);
+        let end = match self.end_field {
+            Some(end_field) => self.text_end(line, separator, end_field),
+            None => line.len(),
+        };
+        line.get(start..end).unwrap_or_default()
+    }
+
+    fn text_start(&self, line: &[u8], separator: Option<u8>) -> usize {
+        let mut i = skip_fields(line, self.start_field, separator, false);
+        if self.skip_start_blanks {
+            i = skip_blanks(line, i);
+        }
+        line.len().min(i + self.start_char)
+    }
+
+    fn text_end(&self, line: &[u8], separator: Option<u8>, end_field: usize) -> usize {
+        // Without an end character the key includes all of the end field.
+        let fields = if self.end_char == 0 {
+            end_field + 1
+        } else {
+            end_field
+        };
+        let mut i = skip_fields(line, fields, separator, self.end_char == 0);
+        if self.end_char == 0 {
+            return i;
+        }
+        if self.skip_end_blanks {
+            i = skip_blanks(line, i);
+        }
+        line.len().min(i + self.end_char)
+    }
+
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        match self.mode {
+            KeyMode::Version => compare_bytes_with(a, b, &VERSION_OPTIONS),
+            KeyMode::Numeric => compare_numeric(a, b),
+            KeyMode::Lexical => a.cmp(b),
+        }
+    }
+}
+
+// skip_fields returns the index after skipping the given number of fields. With a
+// separator, each field ends at the separator, which is skipped too unless keep_last_separator is
+// set and it ends the last field. Without one, each field is a run of blanks followed by a run of
+// non-blanks.
+fn skip_fields(
+    line: &[u8],
+    fields: usize,
+    separator: Option<u8>,
+    keep_last_separator: bool,
+) -> usize {
+    let mut i = 0;
+    for field in 0..fields {
+        if i >= line.len() {
+            break;
+        }
+        match separator {
+            Some(separator) => {
+                i += line[i..]
+                    .iter()
+                    .position(|&c| c == separator)
+                    .unwrap_or(line.len() - i);
+                if i < line.len() && !(keep_last_separator && field + 1 == fields) {
+                    i += 1;
+                }
+            }
+            None => {
+                i = skip_blanks(line, i);
+                i += line[i..].iter().take_while(|&&c| !is_blank(c)).count();
+            }
+        }
+    }
+    i
+}
+
+fn skip_blanks(line: &[u8], i: usize) -> usize {
+    i + line[i..].iter().take_while(|&&c| is_blank(c)).count()
+}
+
+// Like GNU sort in the C locale, fields are separated by spaces, tabs and newlines.
+fn is_blank(c: u8) -> bool {
+    matches!(c, b' ' | b'\t' | b'\n')
+}
+
+// compare_numeric compares the leading decimal numbers of a and b without converting them to
+// floating point, so numbers of any length and precision are supported.
+fn compare_numeric(a: &[u8], b: &[u8]) -> Ordering {
+    let (a_negative, a_int, a_frac) = parse_number(a);
+    let (b_negative, b_int, b_frac) = parse_number(b);
+    let magnitude = || compare_digit_seq(a_int, b_int).then_with(|| a_frac.cmp(b_frac));
+    match (a_negative, b_negative) {
+        (false, false) => magnitude(),
+        (true, true) => magnitude().reverse(),
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+    }
+}
+
+// parse_number splits the leading number of s into its sign, its integer digits and its
+// fractional digits without trailing zeros. Zero is never negative.
+fn parse_number(s: &[u8]) -> (bool, &[u8], &[u8]) {
+    let s = &s[skip_blanks(s, 0)..];
+    let (negative, s) = match s.strip_prefix(b"-") {
+        Some(s) => (true, s),
+        None => (false, s),
+    };
+    let int_len = s.iter().take_while(|c| c.is_ascii_digit()).count();
+    let (int, s) = s.split_at(int_len);
+    let frac = match s.strip_prefix(b".") {
+        Some(s) => &s[..s.iter().take_while(|c| c.is_ascii_digit()).count()],
+        None => b"",
+    };
+    let frac_len = frac.len() - frac.iter().rev().take_while(|&&c| c == b'0').count();
+    let frac = &frac[..frac_len];
+    let is_zero = int.iter().all(|&c| c == b'0') && frac.is_empty();
+    (negative && !is_zero, int, frac)
+}
+
+impl FromStr for Key {
+    type Err = ParseKeyError;
+
+    /// from_str parses a key definition in the syntax of the `-k` option of GNU sort:
+    /// `F[.C][OPTS][,F[.C][OPTS]]`. The supported options are `b`, `n`, `r` and `V`. Like in GNU
+    /// sort, a key with options but neither `n` nor `V` compares lexically, and a key without
+    /// options follows the options of its `KeySpec`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (start, end) = match s.split_once(',') {
+            Some((start, end)) => (start, Some(end)),
+            None => (s, None),
+        };
+
+        let (start_field, start_char, start_options) = parse_position(start)?;
+        let mut key = Key::new(start_field.ok_or(ParseKeyError::FieldZero)?);
+        key.start_char = match start_char {
+            Some(0) => return Err(ParseKeyError::CharZero),
+            Some(start_char) => start_char - 1,
+            None => 0,
+        };
+        // A key with options of its own compares lexically unless the options say otherwise.
+        if s.bytes().any(|c| c.is_ascii_alphabetic()) {
+            key.mode = KeyMode::Lexical;
+            key.inherits = false;
+        }
+        parse_options(&mut key, start_options, true)?;
+
+        if let Some(end) = end {
+            let (end_field, end_char, end_options) = parse_position(end)?;
+            key.end_field = Some(end_field.ok_or(ParseKeyError::FieldZero)? - 1);
+            key.end_char = end_char.unwrap_or(0);
+            parse_options(&mut key, end_options, false)?;
+        }
+        Ok(key)
+    }
+}
+
+// parse_position parses F[.C] and returns the field, which is None if it is 0, the character if
+// there is one, and the options that follow.
+fn parse_position(s: &str) -> Result<(Option<usize>, Option<usize>, &str), ParseKeyError> {
+    let (field, s) = parse_number_prefix(s)?;
+    let (char, s) = match s.strip_prefix('.') {
+        Some(s) => {
+            let (char, s) = parse_number_prefix(s)?;
+            (Some(char), s)
+        }
+        None => (None, s),
+    };
+    Ok(((field > 0).then_some(field), char, s))
+}
+
+fn parse_number_prefix(s: &str) -> Result<(usize, &str), ParseKeyError> {
+    let len = s.bytes().take_while(|c| c.is_ascii_digit()).count();
+    let number = s[..len].parse().map_err(|_| ParseKeyError::InvalidNumber)?;
+    Ok((number, &s[len..]))
+}
+
+// parse_options applies the options of a position to the key.
+fn parse_options(key: &mut Key, options: &str, start: bool) -> Result<(), ParseKeyError> {
+    for option in options.chars() {
+        match option {
+            'b' if start => key.skip_start_blanks = true,
+            'b' => key.skip_end_blanks = true,
+            'n' => key.mode = KeyMode::Numeric,
+            'r' => key.reverse = true,
+            'V' => key.mode = KeyMode::Version,
+            _ => return Err(ParseKeyError::InvalidOption(option)),
+        }
+    }
+    Ok(())
+}
+
+/// ParseKeyError describes why a key definition is not valid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseKeyError {
+    /// A field or character number is missing or too big.
+    InvalidNumber,
+    /// A field number is zero.
+    FieldZero,
+    /// The start character is zero.
+    CharZero,
+    /// The key has an option that is not supported.
+    InvalidOption(char),
+}
+
+impl fmt::Display for ParseKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseKeyError::InvalidNumber => f.write_str("invalid number in key"),
+            ParseKeyError::FieldZero => f.write_str("field number is zero"),
+            ParseKeyError::CharZero => f.write_str("character offset is zero"),
+            ParseKeyError::InvalidOption(c) => write!(f, "invalid key option {:?}", c),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseKeyError {}
+
+/// KeySpec compares lines by a list of keys, like the `-t` and `-k` options of GNU sort. The
+/// first key that differs decides the order. Without keys, the whole line is compared as a
+/// version.
+///
+/// Lines whose keys are all equal compare as equal. Combine the spec with `then_with` to break
+/// such ties, e.g. with a plain byte comparison like GNU sort does.
+/// # Examples
+/// ```
+/// use vsort::comparator::from_fn;
+/// use vsort::{Key, KeySpec, VersionComparator};
+///
+/// fn main() {
+///     // The same as `sort -V -t '\t' -k2,2 -k1,1`.
+///     let spec = KeySpec::new()
+///         .separator(b'\t')
+///         .key(Key::field(2))
+///         .key("1,1".parse().unwrap())
+///         .then_with(from_fn(<[u8]>::cmp));
+///
+///     let mut lines = vec!["gcc\t10.2\tabc", "glibc\t2.31\tdef", "bash\t5.0\t012"];
+///     spec.sort(&mut lines);
+///     assert_eq!(lines, vec!["glibc\t2.31\tdef", "bash\t5.0\t012", "gcc\t10.2\tabc"]);
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct KeySpec {
+    separator: Option<u8>,
+    keys: Vec<Key>,
+    reverse: bool,
+}
+
+impl KeySpec {
+    /// new returns a spec without keys that splits fields at blanks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// separator splits fields at the given byte instead of at the transitions from blanks to
+    /// non-blanks. Without a separator, each field includes the blanks in front of it.
+    pub fn separator(mut self, separator: u8) -> Self {
+        self.separator = Some(separator);
+        self
+    }
+
+    /// key adds a key. Keys are compared in the order they were added.
+    pub fn key(mut self, key: Key) -> Self {
+        self.keys.push(key);
+        self
+    }
+
+    /// reverse reverses the keys that set none of their own options, like the global `-r` of
+    /// GNU sort. Defaults to false.
+    pub fn reverse(mut self, reverse: bool) -> Self {
+        self.reverse = reverse;
+        self
+    }
+}
+
+impl VersionComparator for KeySpec {
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        let whole_line = [Key::new(1)];
+        let keys = if self.keys.is_empty() {
+            &whole_line[..]
+        } else {
+            &self.keys
+        };
+        for key in keys {
+            let cmp = key.compare(key.text(a, self.separator), key.text(b, self.separator));
+            let reverse = if key.inherits {
+                self.reverse
+            } else {
+                key.reverse
+            };
+            let cmp = if reverse { cmp.reverse() } else { cmp };
+            if cmp != Ordering::Equal {
+                return cmp;
+            }
+        }
+        Ordering::Equal
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("2", Key::new(2); "start field")]
+    #[test_case("2,3", Key::new(2).end(3, 0); "end field")]
+    #[test_case("2.3,2.5", Key::field(2).start_char(3).end(2, 5); "characters")]
+    #[test_case("1,1.0", Key::field(1); "end character zero")]
+    #[test_case("2,2V", Key::field(2).mode(KeyMode::Version); "version")]
+    #[test_case("2n,2", Key::field(2).mode(KeyMode::Numeric); "numeric")]
+    #[test_case("2,2r", Key::field(2).mode(KeyMode::Lexical).reverse(true); "reverse is lexical")]
+    #[test_case("2Vr", Key::new(2).mode(KeyMode::Version).reverse(true); "version reverse")]
+    fn test_parse(input: &str, expected: Key) {
+        assert_eq!(input.parse(), Ok(expected));
+    }
+
+    #[test]
+    fn test_parse_blanks() {
+        let key: Key = "2b,3".parse().unwrap();
+        assert!(key.skip_start_blanks && !key.skip_end_blanks);
+        assert_eq!(key.mode, KeyMode::Lexical);
+        let key: Key = "2,3b".parse().unwrap();
+        assert!(!key.skip_start_blanks && key.skip_end_blanks);
+    }
+
+    #[test_case("", ParseKeyError::InvalidNumber; "empty")]
+    #[test_case("a", ParseKeyError::InvalidNumber; "not a number")]
+    #[test_case("0", ParseKeyError::FieldZero; "start field zero")]
+    #[test_case("1,0", ParseKeyError::FieldZero; "end field zero")]
+    #[test_case("1.0", ParseKeyError::CharZero; "start char zero")]
+    #[test_case("1.", ParseKeyError::InvalidNumber; "missing char")]
+    #[test_case("1x", ParseKeyError::InvalidOption('x'); "unknown option")]
+    #[test_case("99999999999999999999999", ParseKeyError::InvalidNumber; "too big")]
+    fn test_parse_errors(input: &str, expected: ParseKeyError) {
+        assert_eq!(input.parse::<Key>(), Err(expected));
+    }
+
+    // The expected texts follow GNU sort, e.g. `echo 'a  b c' | sort -k2,2 --debug`.
+    #[test_case("a  b c", None, "2,2", "  b"; "blank fields include leading blanks")]
+    #[test_case("a  b c", None, "2b,2", "b"; "skip leading blanks")]
+    #[test_case("a  b c", None, "2", "  b c"; "to end of line")]
+    #[test_case("a  b c", None, "4", ""; "missing field")]
+    #[test_case("a:b:c", Some(b':'), "2,2", "b"; "separator")]
+    #[test_case("a:b:c", Some(b':'), "2", "b:c"; "separator to end of line")]
+    #[test_case("a::c", Some(b':'), "2,2", ""; "empty field")]
+    #[test_case("abc:defg", Some(b':'), "2.2,2.3", "ef"; "characters")]
+    #[test_case("abc:defg", Some(b':'), "1.2,2.1", "bc:d"; "characters across fields")]
+    #[test_case("abc:defg", Some(b':'), "1.3,1.1", ""; "end before start")]
+    #[test_case("abc:defg", Some(b':'), "2.9", ""; "start past end of line")]
+    #[test_case("a  bcd", None, "2.2b,2.2b", "c"; "characters after blanks")]
+    fn test_text(line: &str, separator: Option<u8>, key: &str, expected: &str) {
+        let key: Key = key.parse().unwrap();
+        assert_eq!(key.text(line.as_bytes(), separator), expected.as_bytes());
+    }
+
+    #[test_case("10", "9", Ordering::Greater; "integers")]
+    #[test_case("  10", "9", Ordering::Greater; "leading blanks")]
+    #[test_case("-10", "-9", Ordering::Less; "negative")]
+    #[test_case("-1", "0", Ordering::Less; "negative before zero")]
+    #[test_case("-0", "0", Ordering::Equal; "negative zero")]
+    #[test_case("1.5", "1.25", Ordering::Greater; "fractions")]
+    #[test_case("1.50", "1.5", Ordering::Equal; "trailing zeros")]
+    #[test_case("007", "7", Ordering::Equal; "leading zeros")]
+    #[test_case("abc", "0", Ordering::Equal; "no number is zero")]
+    #[test_case(".5", "0.5", Ordering::Equal; "missing integer part")]
+    #[test_case("12345678901234567890123", "12345678901234567890124", Ordering::Less; "big")]
+    fn test_compare_numeric(a: &str, b: &str, expected: Ordering) {
+        assert_eq!(compare_numeric(a.as_bytes(), b.as_bytes()), expected);
+        assert_eq!(
+            compare_numeric(b.as_bytes(), a.as_bytes()),
+            expected.reverse()
+        );
+    }
+
+    #[test]
+    fn test_key_spec() {
+        let spec = KeySpec::new()
+            .separator(b'\t')
+            .key("2,2nr".parse().unwrap())
+            .key(Key::field(1));
+        let mut lines = vec!["b10\t1", "b9\t1", "a\t2", "c\t1.0"];
+        spec.sort(&mut lines);
+        assert_eq!(lines, vec!["a\t2", "b9\t1", "b10\t1", "c\t1.0"]);
+    }
+
+    #[test]
+    fn test_reverse_inherited() {
+        let spec = KeySpec::new()
+            .reverse(true)
+            .key(Key::field(1))
+            .key(Key::field(2).mode(KeyMode::Version));
+        let mut lines = vec!["a 2", "a 10", "b 1"];
+        spec.sort(&mut lines);
+        assert_eq!(lines, vec!["b 1", "a 2", "a 10"]);
+    }
+
+    #[test]
+    fn test_without_keys() {
+        let spec = KeySpec::new();
+        assert_eq!(spec.compare(b"a10", b"a9"), Ordering::Greater);
+        assert_eq!(spec.compare(b"a1", b"a01"), Ordering::Equal);
+    }
+}
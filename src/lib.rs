@@ -1,6 +1,69 @@
-use core::cmp::{Ordering, PartialOrd};
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
-/// sort will sort the given array in place using GNU version sort.
+use core::cmp::{Ordering, PartialOrd};
+#[cfg(feature = "std")]
+use std::ffi::OsStr;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+pub mod apk;
+pub mod arch;
+pub mod comparator;
+#[cfg(feature = "alloc")]
+pub mod deb;
+mod explain;
+#[cfg(feature = "std")]
+mod external;
+#[cfg(feature = "alloc")]
+pub mod gomod;
+#[cfg(feature = "alloc")]
+mod key_spec;
+#[cfg(feature = "alloc")]
+pub mod maven;
+#[cfg(feature = "alloc")]
+mod merge;
+mod options;
+#[cfg(feature = "rayon")]
+mod par;
+#[cfg(feature = "alloc")]
+pub mod pep440;
+pub mod rpm;
+#[cfg(feature = "alloc")]
+pub mod semver;
+#[cfg(feature = "alloc")]
+mod sort_key;
+mod strverscmp;
+mod tokens;
+#[cfg(feature = "alloc")]
+mod version;
+mod version_str;
+
+pub use comparator::VersionComparator;
+pub use explain::{explain, Difference, Explanation, Rule};
+#[cfg(feature = "std")]
+pub use external::ExternalSorter;
+#[cfg(feature = "alloc")]
+pub use key_spec::{Key, KeyMode, KeySpec, ParseKeyError};
+#[cfg(feature = "alloc")]
+pub use merge::{merge_sorted, merge_sorted_with, MergeSorted};
+pub use options::{Comparator, VersionSortOptions};
+#[cfg(feature = "rayon")]
+pub use par::{par_sort, par_sort_by_version_key, par_sort_with};
+#[cfg(feature = "alloc")]
+pub use sort_key::{sort_key, sort_key_bytes};
+pub use strverscmp::{strverscmp, strverscmp_bytes};
+pub use tokens::{split_extension, tokens, FileNameParts, Token, Tokens};
+#[cfg(feature = "alloc")]
+pub use version::Version;
+pub use version_str::VersionStr;
+#[cfg(feature = "alloc")]
+pub use version_str::VersionString;
+
+/// sort will sort the given array in place using GNU version sort. Any element type that can be
+/// viewed as a string slice works, e.g. `&str`, `String` or `Cow<str>`.
 /// # Examples
 /// ```
 /// use vsort::sort;
@@ -22,8 +85,115 @@ use core::cmp::{Ordering, PartialOrd};
 ///     );
 /// }
 /// ```
-pub fn sort(arr: &mut [&str]) {
-    arr.sort_by(|a, b| compare(a, b));
+#[cfg(feature = "alloc")]
+pub fn sort<T: AsRef<str>>(arr: &mut [T]) {
+    arr.sort_by(|a, b| compare(a.as_ref(), b.as_ref()));
+}
+
+/// sort_bytes will sort the given array of byte strings in place using GNU version sort. This
+/// is useful for file names that are not valid UTF-8.
+/// # Examples
+/// ```
+/// use vsort::sort_bytes;
+///
+/// fn main() {
+///     let mut file_names: Vec<&[u8]> = vec![b"b10.txt", b"b\xe91.txt", b"b5.txt"];
+///
+///     sort_bytes(&mut file_names);
+///     assert_eq!(
+///         file_names,
+///         vec![&b"b5.txt"[..], &b"b10.txt"[..], &b"b\xe91.txt"[..]]
+///     );
+/// }
+/// ```
+#[cfg(feature = "alloc")]
+pub fn sort_bytes<T: AsRef<[u8]>>(arr: &mut [T]) {
+    arr.sort_by(|a, b| compare_bytes(a.as_ref(), b.as_ref()));
+}
+
+/// sort_paths will sort the given paths in place using GNU version sort. Paths are compared by
+/// their raw bytes, so names that are not valid UTF-8 keep their correct ordering.
+/// # Examples
+/// ```
+/// use std::path::PathBuf;
+/// use vsort::sort_paths;
+///
+/// fn main() {
+///     let mut paths: Vec<PathBuf> = vec!["b10.txt".into(), "b5.txt".into(), ".hidden".into()];
+///
+///     sort_paths(&mut paths);
+///     assert_eq!(paths, vec![PathBuf::from(".hidden"), "b5.txt".into(), "b10.txt".into()]);
+/// }
+/// ```
+#[cfg(feature = "std")]
+pub fn sort_paths<T: AsRef<Path>>(arr: &mut [T]) {
+    arr.sort_by(|a, b| compare_paths(a.as_ref(), b.as_ref()));
+}
+
+/// sort_by_version_key will sort the given array in place using GNU version sort on the key
+/// extracted from each element. Like `slice::sort_by_key`, the sort is stable and the key is
+/// extracted on every comparison.
+/// # Examples
+/// ```
+/// use vsort::sort_by_version_key;
+///
+/// struct Package {
+///     name: String,
+///     size: u64,
+/// }
+///
+/// fn main() {
+///     let mut packages = vec![
+///         Package { name: "gcc-10.2".to_string(), size: 30 },
+///         Package { name: "gcc-9.4".to_string(), size: 20 },
+///     ];
+///
+///     sort_by_version_key(&mut packages, |p| p.name.clone());
+///     assert_eq!(packages[0].name, "gcc-9.4");
+///     assert_eq!(packages[1].size, 30);
+/// }
+/// ```
+#[cfg(feature = "alloc")]
+pub fn sort_by_version_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    K: AsRef<str>,
+    F: FnMut(&T) -> K,
+{
+    arr.sort_by(|a, b| compare(f(a).as_ref(), f(b).as_ref()));
+}
+
+/// sort_unstable_by_version_key is like `sort_by_version_key`, but does not preserve the order of
+/// elements with equal keys. It does not allocate.
+pub fn sort_unstable_by_version_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    K: AsRef<str>,
+    F: FnMut(&T) -> K,
+{
+    arr.sort_unstable_by(|a, b| compare(f(a).as_ref(), f(b).as_ref()));
+}
+
+/// sort_by_cached_version_key is like `sort_by_version_key`, but extracts and tokenizes each key
+/// only once. This is faster when key extraction is expensive or the array is large, at the cost
+/// of an allocation per element.
+/// # Examples
+/// ```
+/// use std::path::PathBuf;
+/// use vsort::sort_by_cached_version_key;
+///
+/// fn main() {
+///     let mut paths: Vec<PathBuf> = vec!["logs/app-10.log".into(), "logs/app-9.log".into()];
+///
+///     sort_by_cached_version_key(&mut paths, |p| p.to_string_lossy().into_owned());
+///     assert_eq!(paths, vec![PathBuf::from("logs/app-9.log"), "logs/app-10.log".into()]);
+/// }
+/// ```
+#[cfg(feature = "alloc")]
+pub fn sort_by_cached_version_key<T, K, F>(arr: &mut [T], mut f: F)
+where
+    K: AsRef<str>,
+    F: FnMut(&T) -> K,
+{
+    arr.sort_by_cached_key(|a| Version::new(f(a).as_ref()).into_owned());
 }
 
 /// compare implements GNU version sort.
@@ -50,6 +220,28 @@ pub fn sort(arr: &mut [&str]) {
 /// }
 /// ```
 pub fn compare(a: &str, b: &str) -> Ordering {
+    compare_bytes(a.as_bytes(), b.as_bytes())
+}
+
+/// compare_bytes implements GNU version sort on raw bytes, like gnulib's `filevercmp`. The
+/// inputs do not need to be valid UTF-8.
+/// # Examples
+/// ```
+/// use std::cmp::Ordering;
+/// use vsort::compare_bytes;
+///
+/// fn main() {
+///     // Latin-1 encoded names are not valid UTF-8 but still compare by their numbers.
+///     assert_eq!(compare_bytes(b"caf\xe9 2.txt", b"caf\xe9 10.txt"), Ordering::Less);
+/// }
+/// ```
+pub fn compare_bytes(a: &[u8], b: &[u8]) -> Ordering {
+    compare_bytes_with(a, b, &VersionSortOptions::new())
+}
+
+// compare_bytes_with implements GNU version sort with the rules that the given options leave
+// enabled. Reversing the result is left to the caller.
+fn compare_bytes_with(a: &[u8], b: &[u8], options: &VersionSortOptions) -> Ordering {
     // Let's shadow the inputs for easy reference.
     let mut a = a;
     let mut b = b;
@@ -58,13 +250,13 @@ pub fn compare(a: &str, b: &str) -> Ordering {
     // all other strings, in the listed order: ("", ".", "..").
     // https://github.com/coreutils/coreutils/blob/master/doc/sort-version.texi#L532-L569
     if let Some(cmp) = match (a, b) {
-        ("", "") | (".", ".") | ("..", "..") => Some(Ordering::Equal),
-        ("", _) => Some(Ordering::Less),
-        (_, "") => Some(Ordering::Greater),
-        (".", _) => Some(Ordering::Less),
-        (_, ".") => Some(Ordering::Greater),
-        ("..", _) => Some(Ordering::Less),
-        (_, "..") => Some(Ordering::Greater),
+        (b"", b"") | (b".", b".") | (b"..", b"..") => Some(Ordering::Equal),
+        (b"", _) => Some(Ordering::Less),
+        (_, b"") => Some(Ordering::Greater),
+        (b".", _) => Some(Ordering::Less),
+        (_, b".") => Some(Ordering::Greater),
+        (b"..", _) => Some(Ordering::Less),
+        (_, b"..") => Some(Ordering::Greater),
         _ => None,
     } {
         return cmp;
@@ -72,50 +264,92 @@ pub fn compare(a: &str, b: &str) -> Ordering {
 
     // Hidden files get priority. If both files are hidden then we remove the leading period
     // and compare.
-    match (a.starts_with('.'), b.starts_with('.')) {
-        (true, false) => return Ordering::Less,
-        (false, true) => return Ordering::Greater,
-        (false, false) => {}
-        (true, true) => {
-            a = if a.len() == 1 { "" } else { &a[1..] };
-            b = if b.len() == 1 { "" } else { &b[1..] };
+    if options.hidden_priority {
+        match (a.starts_with(b"."), b.starts_with(b".")) {
+            (true, false) => return Ordering::Less,
+            (false, true) => return Ordering::Greater,
+            (false, false) => {}
+            (true, true) => {
+                a = &a[1..];
+                b = &b[1..];
+            }
         }
     }
 
     // Compare without the file extensions
-    let cmp = sequence_cmp(split_extension(a).0, split_extension(b).0);
-    if cmp != Ordering::Equal {
-        return cmp;
+    if options.strip_extensions {
+        let cmp = sequence_cmp(split_ext(a).0, split_ext(b).0, options.ignore_case);
+        if cmp != Ordering::Equal {
+            return cmp;
+        }
     }
     // Compare the original strings with the file extensions
-    let cmp = sequence_cmp(a, b);
+    let cmp = sequence_cmp(a, b, options.ignore_case);
     if cmp != Ordering::Equal {
         return cmp;
     }
     // At this point the file extensions are the same, so we compare the full strings.
     // this helps with cases like a0001 and a1 so that they have a consistent ordering.
-    a.cmp(b)
+    if options.byte_tie_break {
+        return a.cmp(b);
+    }
+    Ordering::Equal
+}
+
+/// compare_os_str implements GNU version sort for OS strings, such as the names returned by
+/// `std::fs::read_dir`. The strings are compared by their encoded bytes, which on Unix are the
+/// file name bytes as stored on disk.
+/// # Examples
+/// ```
+/// use std::cmp::Ordering;
+/// use std::ffi::OsStr;
+/// use vsort::compare_os_str;
+///
+/// fn main() {
+///     assert_eq!(compare_os_str(OsStr::new("b 5.txt"), OsStr::new("b 10.txt")), Ordering::Less);
+/// }
+/// ```
+#[cfg(feature = "std")]
+pub fn compare_os_str(a: &OsStr, b: &OsStr) -> Ordering {
+    compare_bytes(a.as_encoded_bytes(), b.as_encoded_bytes())
+}
+
+/// compare_paths implements GNU version sort for paths. The whole path is compared, just like
+/// `sort -V` would compare each line of a `find` listing.
+/// # Examples
+/// ```
+/// use std::cmp::Ordering;
+/// use std::path::Path;
+/// use vsort::compare_paths;
+///
+/// fn main() {
+///     assert_eq!(
+///         compare_paths(Path::new("logs/app-9.log"), Path::new("logs/app-10.log")),
+///         Ordering::Less
+///     );
+/// }
+/// ```
+#[cfg(feature = "std")]
+pub fn compare_paths(a: &Path, b: &Path) -> Ordering {
+    compare_os_str(a.as_os_str(), b.as_os_str())
 }
 
 /// sequence_cmp extracts non-digit and digit sequences from the two strings and compares the
 /// sequences until an ordering is determined.
-fn sequence_cmp(a: &str, b: &str) -> Ordering {
+fn sequence_cmp(a: &[u8], b: &[u8], ignore_case: bool) -> Ordering {
     let mut a_str = a;
     let mut b_str = b;
     loop {
         let (a_non_digit_part, remaining_a) = non_digit_seq(a_str);
         let (b_non_digit_part, remaining_b) = non_digit_seq(b_str);
-        let cmp = compare_non_digit_seq(a_non_digit_part, b_non_digit_part);
+        let cmp = compare_non_digit_seq(a_non_digit_part, b_non_digit_part, ignore_case);
         if cmp != Ordering::Equal {
             return cmp;
         }
         let (a_digit_part, remaining_a) = digit_seq(remaining_a);
         let (b_digit_part, remaining_b) = digit_seq(remaining_b);
 
-        // According to the docs, a missing numerical part also counts as zero.
-        let a_digits = a_digit_part.parse::<u64>().unwrap_or_default();
-        let b_digits = b_digit_part.parse::<u64>().unwrap_or_default();
-        let cmp = a_digits.cmp(&b_digits);
+        let cmp = compare_digit_seq(a_digit_part, b_digit_part);
         if cmp != Ordering::Equal {
             return cmp;
         }
@@ -130,6 +364,25 @@ fn sequence_cmp(a: &str, b: &str) -> Ordering {
     }
 }
 
+/// compare_digit_seq compares two sequences of ASCII digits by their numerical value. Like
+/// gnulib's `verrevcmp`, the digits are never parsed into an integer, so numbers of any length
+/// are supported. Leading zeros are ignored and, according to the docs, a missing numerical
+/// part also counts as zero.
+fn compare_digit_seq(a: &[u8], b: &[u8]) -> Ordering {
+    let a = strip_leading_zeros(a);
+    let b = strip_leading_zeros(b);
+    // Without leading zeros the longer number is the larger one. Numbers of the same length
+    // compare the same way their digits do.
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+fn strip_leading_zeros(digits: &[u8]) -> &[u8] {
+    digits
+        .iter()
+        .position(|&c| c != b'0')
+        .map_or(b"", |index| &digits[index..])
+}
+
 /*
 fn split_extension(s: &str) -> (&str, &str) {
     // According to GNU sort, an extension is defined as a dot, followed by an
@@ -145,36 +398,36 @@ fn split_extension(s: &str) -> (&str, &str) {
 }
  */
 
-fn split_extension(s: &str) -> (&str, &str) {
+fn split_ext(s: &[u8]) -> (&[u8], &[u8]) {
     // According to GNU sort, an extension is defined as a dot, followed by an
     // ASCII letter or tilde, followed by zero or more ASCII letters, digits,
     // or tildes; all repeated zero or more times, and ending at string end.
     // The regex is from https://github.com/coreutils/coreutils/blob/master/doc/sort-version.texi#L584-L591
     let mut split_ind: Option<usize> = None;
-    let mut last_char: Option<char> = None;
-    for (i, c) in s.char_indices().rev() {
+    let mut last_char: Option<u8> = None;
+    for (i, &c) in s.iter().enumerate().rev() {
         // If we have found a period
-        if c == '.' {
+        if c == b'.' {
             match last_char {
                 // We found a period as our last character. Exit with no extension
-                None => return (s, ""),
+                None => return (s, b""),
                 Some(prev_char) => {
                     // If the previous character wasn't alphanumeric this isn't a valid
-                    if prev_char.is_ascii_alphabetic() || prev_char == '~' {
+                    if prev_char.is_ascii_alphabetic() || prev_char == b'~' {
                         split_ind = Some(i);
                     } else {
                         break;
                     }
                 }
             }
-        } else if !(c.is_ascii_alphanumeric() || c == '~') {
+        } else if !(c.is_ascii_alphanumeric() || c == b'~') {
             break;
         }
         // Update the last char for inspection
         last_char = Some(c);
     }
 
-    split_ind.map_or((s, ""), |ind| s.split_at(ind))
+    split_ind.map_or((s, b""), |ind| s.split_at(ind))
 }
 
 #[derive(Eq)]
@@ -236,9 +489,18 @@ impl PartialEq for VersionSortChar {
     }
 }
 
-fn compare_non_digit_seq(a: &str, b: &str) -> Ordering {
-    let mut a_bytes = a.bytes();
-    let mut b_bytes = b.bytes();
+// compare_non_digit_seq compares two non-digit sequences character by character. When
+// ignore_case is set, ASCII letters are compared as if they were lowercase.
+fn compare_non_digit_seq(a: &[u8], b: &[u8], ignore_case: bool) -> Ordering {
+    let fold = |c: u8| {
+        if ignore_case {
+            c.to_ascii_lowercase()
+        } else {
+            c
+        }
+    };
+    let mut a_bytes = a.iter().copied().map(fold);
+    let mut b_bytes = b.iter().copied().map(fold);
     loop {
         let a_byte = a_bytes.next();
         let b_byte = b_bytes.next();
@@ -255,22 +517,23 @@ fn compare_non_digit_seq(a: &str, b: &str) -> Ordering {
     }
 }
 
-fn non_digit_seq(a: &str) -> (&str, &str) {
-    a.bytes()
-        .enumerate()
-        .find(|(_, c)| c.is_ascii_digit())
-        .map_or((a, ""), |(index, _)| a.split_at(index))
+fn non_digit_seq(a: &[u8]) -> (&[u8], &[u8]) {
+    a.iter()
+        .position(|c| c.is_ascii_digit())
+        .map_or((a, b""), |index| a.split_at(index))
 }
 
-fn digit_seq(a: &str) -> (&str, &str) {
-    a.bytes()
-        .enumerate()
-        .find(|(_, c)| !c.is_ascii_digit())
-        .map_or((a, ""), |(index, _)| a.split_at(index))
+fn digit_seq(a: &[u8]) -> (&[u8], &[u8]) {
+    a.iter()
+        .position(|c| !c.is_ascii_digit())
+        .map_or((a, b""), |index| a.split_at(index))
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod test {
+    use std::borrow::Cow;
+    use std::path::PathBuf;
+
     use test_case::test_case;
 
     use super::*;
@@ -286,7 +549,7 @@ mod test {
     #[test]
     fn test_non_digit_sorting() {
         let mut list = vec!["aaa", "aa", "aab", "aa&", "aa_", "aa~", "a"];
-        list.sort_by(|a, b| compare_non_digit_seq(a, b));
+        list.sort_by(|a, b| compare_non_digit_seq(a.as_bytes(), b.as_bytes(), false));
 
         assert_eq!(
             list,
@@ -300,18 +563,18 @@ mod test {
 
     #[test]
     fn test_non_digit_seq() {
-        let a = "file_1.txt";
+        let a = b"file_1.txt";
         let (seq, remainder) = non_digit_seq(a);
-        assert_eq!(seq, "file_");
-        assert_eq!(remainder, "1.txt");
+        assert_eq!(seq, b"file_");
+        assert_eq!(remainder, b"1.txt");
 
         let (seq, remainder) = non_digit_seq(&a[5..]);
-        assert_eq!(seq, "");
-        assert_eq!(remainder, "1.txt");
+        assert_eq!(seq, b"");
+        assert_eq!(remainder, b"1.txt");
 
         let (seq, remainder) = non_digit_seq(&a[6..]);
-        assert_eq!(seq, ".txt");
-        assert_eq!(remainder, "");
+        assert_eq!(seq, b".txt");
+        assert_eq!(remainder, b"");
     }
 
     #[test]
@@ -339,6 +602,179 @@ mod test {
         assert_eq!(list, expected);
     }
 
+    // Latin-1 encoded names are not valid UTF-8, but they should still be ordered by the same
+    // rules as their UTF-8 counterparts.
+    #[test_case(
+      vec![b"caf\xe9 10.txt", b"caf\xe9 2.txt", b"caf\xe9.txt", b".caf\xe9"],
+      vec![b".caf\xe9", b"caf\xe9.txt", b"caf\xe9 2.txt", b"caf\xe9 10.txt"];
+      "latin-1 file names"
+    )]
+    #[test_case(
+      vec![b"\xff\xfe1", b"a\xff", b"a~", b"a", b"..", b"."],
+      vec![b".", b"..", b"a~", b"a", b"a\xff", b"\xff\xfe1"];
+      "invalid utf-8 bytes sort after ascii letters"
+    )]
+    fn test_sort_bytes(original: Vec<&[u8]>, expected: Vec<&[u8]>) {
+        let mut list = original;
+        sort_bytes(&mut list);
+        assert_eq!(list, expected);
+    }
+
+    #[test_case("a.txt", "b 1.txt"; "letters")]
+    #[test_case("b 5.txt", "b 10.txt"; "numbers")]
+    #[test_case(".zz.~1~", "\u{1}.0"; "hidden file")]
+    #[test_case("gcc-c++-10.fc9.tar.gz", "gcc-c++-10.8.12-0.7rc2.fc9.tar.bz2"; "extensions")]
+    fn test_compare_matches_compare_bytes(a: &str, b: &str) {
+        assert_eq!(compare(a, b), compare_bytes(a.as_bytes(), b.as_bytes()));
+        assert_eq!(compare(b, a), compare_bytes(b.as_bytes(), a.as_bytes()));
+    }
+
+    #[test]
+    fn test_sort_owned_and_cow_strings() {
+        let mut owned: Vec<String> = vec!["b 10.txt".into(), "b 5.txt".into(), "a.txt".into()];
+        sort(&mut owned);
+        assert_eq!(owned, vec!["a.txt", "b 5.txt", "b 10.txt"]);
+
+        let mut cows: Vec<Cow<str>> = vec![Cow::Owned("1.10".into()), Cow::Borrowed("1.9")];
+        sort(&mut cows);
+        assert_eq!(cows, vec!["1.9", "1.10"]);
+
+        let mut bytes: Vec<Vec<u8>> = vec![b"a\xff10".to_vec(), b"a\xff9".to_vec()];
+        sort_bytes(&mut bytes);
+        assert_eq!(bytes, vec![b"a\xff9".to_vec(), b"a\xff10".to_vec()]);
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Package {
+        name: &'static str,
+        id: usize,
+    }
+
+    fn packages() -> Vec<Package> {
+        [
+            "gcc-10.fc9.tar.gz",
+            "gcc-9.tar.gz",
+            "gcc-10.fc9.tar.gz",
+            "gcc-9.0.tar.gz",
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(id, name)| Package { name, id })
+        .collect()
+    }
+
+    #[test]
+    fn test_sort_by_version_key() {
+        let mut list = packages();
+        sort_by_version_key(&mut list, |p| p.name);
+        let ids: Vec<usize> = list.iter().map(|p| p.id).collect();
+        // The sort is stable, so the two equal names keep their original order.
+        assert_eq!(ids, vec![1, 3, 0, 2]);
+
+        let mut list = packages();
+        sort_by_cached_version_key(&mut list, |p| p.name.to_string());
+        let ids: Vec<usize> = list.iter().map(|p| p.id).collect();
+        assert_eq!(ids, vec![1, 3, 0, 2]);
+
+        let mut list = packages();
+        sort_unstable_by_version_key(&mut list, |p| p.name);
+        let names: Vec<&str> = list.iter().map(|p| p.name).collect();
+        assert_eq!(
+            names,
+            vec![
+                "gcc-9.tar.gz",
+                "gcc-9.0.tar.gz",
+                "gcc-10.fc9.tar.gz",
+                "gcc-10.fc9.tar.gz"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_paths() {
+        let mut paths: Vec<PathBuf> = vec![
+            "dir/file10.txt".into(),
+            "dir/file9.txt".into(),
+            "dir/.config".into(),
+            "dir/file9.txt~".into(),
+            "dir".into(),
+        ];
+        sort_paths(&mut paths);
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("dir"),
+                "dir/.config".into(),
+                // The tilde is part of the extension and sorts before the end of the string.
+                "dir/file9.txt~".into(),
+                "dir/file9.txt".into(),
+                "dir/file10.txt".into(),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_compare_os_str_non_utf8() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let a = OsStr::from_bytes(b"caf\xe9 2.txt");
+        let b = OsStr::from_bytes(b"caf\xe9 10.txt");
+        assert_eq!(compare_os_str(a, b), Ordering::Less);
+        assert_eq!(compare_paths(Path::new(b), Path::new(a)), Ordering::Greater);
+        // A lossy conversion replaces the invalid byte, which changes the ordering relative to
+        // other names that start with letters.
+        let c = OsStr::new("cafz");
+        assert_eq!(compare_os_str(c, a), Ordering::Less);
+    }
+
+    #[test_case("0", "", Ordering::Equal; "zero is the same as a missing number")]
+    #[test_case("000", "0", Ordering::Equal; "leading zeros are ignored")]
+    #[test_case("0010", "9", Ordering::Greater; "leading zeros do not make a number longer")]
+    #[test_case("18446744073709551615", "18446744073709551616", Ordering::Less; "u64 max")]
+    #[test_case("18446744073709551616", "9", Ordering::Greater; "just above u64 max")]
+    #[test_case(
+      "99999999999999999999999999999999999999",
+      "100000000000000000000000000000000000000",
+      Ordering::Less;
+      "longer numbers are larger"
+    )]
+    #[test_case(
+      "123456789012345678901234567890",
+      "123456789012345678901234567891",
+      Ordering::Less;
+      "same length compares digits"
+    )]
+    fn test_compare_digit_seq(a: &str, b: &str, expected: Ordering) {
+        assert_eq!(compare_digit_seq(a.as_bytes(), b.as_bytes()), expected);
+        assert_eq!(
+            compare_digit_seq(b.as_bytes(), a.as_bytes()),
+            expected.reverse()
+        );
+    }
+
+    #[test]
+    fn test_numbers_above_u64_max() {
+        let mut list = vec![
+            "build-100000000000000000000.tar.gz",
+            "build-18446744073709551616.tar.gz",
+            "build-18446744073709551615.tar.gz",
+            "build-2.tar.gz",
+            "build-018446744073709551617.tar.gz",
+        ];
+        sort(&mut list);
+        assert_eq!(
+            list,
+            vec![
+                "build-2.tar.gz",
+                "build-18446744073709551615.tar.gz",
+                "build-18446744073709551616.tar.gz",
+                "build-018446744073709551617.tar.gz",
+                "build-100000000000000000000.tar.gz",
+            ]
+        );
+    }
+
     #[test]
     fn test_missing_number_part() {
         let mut original_list = vec!["file.txt", "file0.txt"];
@@ -419,87 +855,92 @@ mod test {
     #[test_case("a.#$%", ("a.#$%", ""); "no extension present")]
     #[test_case("a.#$%.txt", ("a.#$%", ".txt"); "extension stops at non-alphanumeric characters")]
     fn test_split_extension(input: &str, split: (&str, &str)) {
-        assert_eq!(split_extension(input), split);
+        assert_eq!(
+            split_ext(input.as_bytes()),
+            (split.0.as_bytes(), split.1.as_bytes())
+        );
     }
 
     // This list is pulled from
     // https://github.com/coreutils/gnulib/blob/master/tests/test-filevercmp.c#L26-L102
+    pub(crate) const GNULIB_SORTED: &[&str] = &[
+        "",
+        ".",
+        "..",
+        ".0",
+        ".9",
+        ".A",
+        ".Z",
+        ".a~",
+        ".a",
+        ".b~",
+        ".b",
+        ".z",
+        ".zz~",
+        ".zz",
+        ".zz.~1~",
+        ".zz.0",
+        ".\u{1}",
+        ".\u{1}.txt",
+        ".\u{1}x",
+        ".\u{1}x\u{1}",
+        ".\u{1}.0",
+        "0",
+        "9",
+        "A",
+        "Z",
+        "a~",
+        "a",
+        "a.b~",
+        "a.b",
+        "a.bc~",
+        "a.bc",
+        "a+",
+        "a.",
+        "a..a",
+        "a.+",
+        "b~",
+        "b",
+        "gcc-c++-10.fc9.tar.gz",
+        "gcc-c++-10.fc9.tar.gz.~1~",
+        "gcc-c++-10.fc9.tar.gz.~2~",
+        "gcc-c++-10.8.12-0.7rc2.fc9.tar.bz2",
+        "gcc-c++-10.8.12-0.7rc2.fc9.tar.bz2.~1~",
+        "glibc-2-0.1.beta1.fc10.rpm",
+        "glibc-common-5-0.2.beta2.fc9.ebuild",
+        "glibc-common-5-0.2b.deb",
+        "glibc-common-11b.ebuild",
+        "glibc-common-11-0.6rc2.ebuild",
+        "libstdc++-0.5.8.11-0.7rc2.fc10.tar.gz",
+        "libstdc++-4a.fc8.tar.gz",
+        "libstdc++-4.10.4.20040204svn.rpm",
+        "libstdc++-devel-3.fc8.ebuild",
+        "libstdc++-devel-3a.fc9.tar.gz",
+        "libstdc++-devel-8.fc8.deb",
+        "libstdc++-devel-8.6.2-0.4b.fc8",
+        "nss_ldap-1-0.2b.fc9.tar.bz2",
+        "nss_ldap-1-0.6rc2.fc8.tar.gz",
+        "nss_ldap-1.0-0.1a.tar.gz",
+        "nss_ldap-10beta1.fc8.tar.gz",
+        "nss_ldap-10.11.8.6.20040204cvs.fc10.ebuild",
+        "z",
+        "zz~",
+        "zz",
+        "zz.~1~",
+        "zz.0",
+        "zz.0.txt",
+        "\u{1}",
+        "\u{1}.txt",
+        "\u{1}x",
+        "\u{1}x\u{1}",
+        "\u{1}.0",
+        "#\u{1}.b#",
+        "#.b#",
+    ];
+
     #[test]
     fn test_long_sorted_list() {
-        let expected = vec![
-            "",
-            ".",
-            "..",
-            ".0",
-            ".9",
-            ".A",
-            ".Z",
-            ".a~",
-            ".a",
-            ".b~",
-            ".b",
-            ".z",
-            ".zz~",
-            ".zz",
-            ".zz.~1~",
-            ".zz.0",
-            ".\u{1}",
-            ".\u{1}.txt",
-            ".\u{1}x",
-            ".\u{1}x\u{1}",
-            ".\u{1}.0",
-            "0",
-            "9",
-            "A",
-            "Z",
-            "a~",
-            "a",
-            "a.b~",
-            "a.b",
-            "a.bc~",
-            "a.bc",
-            "a+",
-            "a.",
-            "a..a",
-            "a.+",
-            "b~",
-            "b",
-            "gcc-c++-10.fc9.tar.gz",
-            "gcc-c++-10.fc9.tar.gz.~1~",
-            "gcc-c++-10.fc9.tar.gz.~2~",
-            "gcc-c++-10.8.12-0.7rc2.fc9.tar.bz2",
-            "gcc-c++-10.8.12-0.7rc2.fc9.tar.bz2.~1~",
-            "glibc-2-0.1.beta1.fc10.rpm",
-            "glibc-common-5-0.2.beta2.fc9.ebuild",
-            "glibc-common-5-0.2b.deb",
-            "glibc-common-11b.ebuild",
-            "glibc-common-11-0.6rc2.ebuild",
-            "libstdc++-0.5.8.11-0.7rc2.fc10.tar.gz",
-            "libstdc++-4a.fc8.tar.gz",
-            "libstdc++-4.10.4.20040204svn.rpm",
-            "libstdc++-devel-3.fc8.ebuild",
-            "libstdc++-devel-3a.fc9.tar.gz",
-            "libstdc++-devel-8.fc8.deb",
-            "libstdc++-devel-8.6.2-0.4b.fc8",
-            "nss_ldap-1-0.2b.fc9.tar.bz2",
-            "nss_ldap-1-0.6rc2.fc8.tar.gz",
-            "nss_ldap-1.0-0.1a.tar.gz",
-            "nss_ldap-10beta1.fc8.tar.gz",
-            "nss_ldap-10.11.8.6.20040204cvs.fc10.ebuild",
-            "z",
-            "zz~",
-            "zz",
-            "zz.~1~",
-            "zz.0",
-            "zz.0.txt",
-            "\u{1}",
-            "\u{1}.txt",
-            "\u{1}x",
-            "\u{1}x\u{1}",
-            "\u{1}.0",
-            "#\u{1}.b#",
-            "#.b#",
-        ];
+        let expected = GNULIB_SORTED.to_vec();
         let mut list = expected.clone();
         list.reverse();
         assert_ne!(list, expected);
@@ -520,7 +961,10 @@ mod test {
         let end = list.len();
         for i in 0..end {
             for j in (i + 1)..end {
-                assert_eq!(sequence_cmp(list[i], list[j]), Ordering::Equal);
+                assert_eq!(
+                    sequence_cmp(list[i].as_bytes(), list[j].as_bytes(), false),
+                    Ordering::Equal
+                );
             }
         }
     }
@@ -0,0 +1,430 @@
+//! Maven artifact version ordering, as implemented by Maven's `ComparableVersion`.
+//!
+//! Versions are split into items at periods, hyphens and transitions between digits and
+//! non-digits. Hyphens and transitions start a nested list, so `1.0-beta-2` is read as
+//! `[1, 0, [beta, [2]]]`. Numbers compare by value and sort after qualifiers, which are ranked
+//! `alpha < beta < milestone < rc < snapshot < "" < sp`, followed by unknown qualifiers in
+//! lexical order. The release qualifiers `ga`, `final` and `release` are aliases for the empty
+//! qualifier, `cr` is an alias for `rc`, and `a`, `b` and `m` directly followed by a number are
+//! short for `alpha`, `beta` and `milestone`. Comparison is case insensitive.
+//! https://maven.apache.org/pom.html#version-order-specification
+use core::cmp::Ordering;
+use core::fmt;
+use core::mem;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::{compare_digit_seq, digit_seq, non_digit_seq, strip_leading_zeros};
+
+// The known qualifiers in the order they sort in. Unknown qualifiers sort after all of them.
+const QUALIFIERS: &[&str] = &["alpha", "beta", "milestone", "rc", "snapshot", "", "sp"];
+const ALIASES: &[(&str, &str)] = &[("ga", ""), ("final", ""), ("release", ""), ("cr", "rc")];
+// The rank of the empty qualifier, which is what a missing item compares like.
+const RELEASE: (usize, &str) = (5, "");
+
+/// MavenVersion is a parsed Maven artifact version. Parsing never fails, since Maven accepts any
+/// string as a version.
+///
+/// Versions that only differ in their spelling, e.g. `1.0` and `1-ga`, are equal.
+/// # Examples
+/// ```
+/// use vsort::maven::MavenVersion;
+///
+/// fn main() {
+///     let version = MavenVersion::new("2.0-M1");
+///     assert_eq!(version.canonical(), "2-milestone-1");
+///     assert!(version < MavenVersion::new("2.0-SNAPSHOT"));
+///     assert_eq!(MavenVersion::new("2.0.RELEASE"), MavenVersion::new("2"));
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MavenVersion {
+    value: String,
+    items: Vec<Item>,
+}
+
+#[derive(Clone, Debug)]
+enum Item {
+    // The digits of a number without leading zeros, which are empty for 0.
+    Int(String),
+    // A qualifier in lowercase with aliases resolved.
+    Str(String),
+    List(Vec<Item>),
+}
+
+impl MavenVersion {
+    /// new parses the given version.
+    pub fn new(s: &str) -> Self {
+        Self {
+            value: s.to_string(),
+            items: parse(s),
+        }
+    }
+
+    /// as_str returns the version as it was given.
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    /// canonical returns the normalized form of the version that Maven uses to check versions for
+    /// equality, e.g. `1-alpha-2` for `1.0a2`.
+    pub fn canonical(&self) -> String {
+        let mut canonical = String::new();
+        write_items(&mut canonical, &self.items);
+        canonical
+    }
+}
+
+// parse follows ComparableVersion.parseVersion.
+fn parse(s: &str) -> Vec<Item> {
+    let lower = s.to_lowercase();
+    // Every hyphen and every transition between digits and non-digits starts a list that is
+    // nested in the current one. Since nothing is added to a list once a nested list starts, the
+    // lists form a chain, and each nested list is added to its parent at the end.
+    let mut parents: Vec<Vec<Item>> = Vec::new();
+    let mut current = Vec::new();
+    let mut rest = lower.as_bytes();
+    loop {
+        let end = rest
+            .iter()
+            .position(|&c| c == b'.' || c == b'-')
+            .unwrap_or(rest.len());
+        let (mut token, tail) = rest.split_at(end);
+        let separator = tail.first().copied();
+        if token.is_empty() && separator.is_some() {
+            current.push(Item::Int(String::new()));
+        }
+        while !token.is_empty() {
+            let is_digit = token[0].is_ascii_digit();
+            let (run, next) = if is_digit {
+                digit_seq(token)
+            } else {
+                non_digit_seq(token)
+            };
+            // Digit and non-digit runs alternate, so a run is followed by a digit if it is not
+            // the last one in the token.
+            current.push(parse_item(run, is_digit, !next.is_empty()));
+            if !next.is_empty() {
+                parents.push(mem::take(&mut current));
+            }
+            token = next;
+        }
+        match separator {
+            Some(b'-') => parents.push(mem::take(&mut current)),
+            Some(_) => {}
+            None => break,
+        }
+        rest = &tail[1..];
+    }
+
+    normalize(&mut current);
+    while let Some(mut parent) = parents.pop() {
+        parent.push(Item::List(current));
+        normalize(&mut parent);
+        current = parent;
+    }
+    current
+}
+
+fn parse_item(run: &[u8], is_digit: bool, followed_by_digit: bool) -> Item {
+    // Runs are split at ASCII bytes, so they are valid UTF-8.
+    if is_digit {
+        let digits = strip_leading_zeros(run);
+        return Item::Int(String::from_utf8_lossy(digits).into_owned());
+    }
+    let run = String::from_utf8_lossy(run);
+    let qualifier = match &*run {
+        "a" if followed_by_digit => "alpha",
+        "b" if followed_by_digit => "beta",
+        "m" if followed_by_digit => "milestone",
+        run => ALIASES
+            .iter()
+            .find(|&&(alias, _)| alias == run)
+            .map_or(run, |&(_, qualifier)| qualifier),
+    };
+    Item::Str(qualifier.to_string())
+}
+
+// normalize removes trailing items that are equal to a missing item: zeros, release qualifiers
+// and empty lists. Nested lists do not stop the removal, so "1.0-a" becomes "1-a".
+fn normalize(items: &mut Vec<Item>) {
+    for i in (0..items.len()).rev() {
+        if items[i].is_null() {
+            items.remove(i);
+        } else if !matches!(items[i], Item::List(_)) {
+            break;
+        }
+    }
+}
+
+impl Item {
+    fn is_null(&self) -> bool {
+        match self {
+            Item::Int(digits) => digits.is_empty(),
+            Item::Str(qualifier) => qualifier_rank(qualifier) == RELEASE,
+            Item::List(items) => items.is_empty(),
+        }
+    }
+
+    // compare compares the item with another item, or with a missing item if the other list
+    // has already ended.
+    fn compare(&self, other: Option<&Item>) -> Ordering {
+        match (self, other) {
+            (Item::Int(digits), None) => {
+                if digits.is_empty() {
+                    Ordering::Equal
+                } else {
+                    Ordering::Greater
+                }
+            }
+            (Item::Int(a), Some(Item::Int(b))) => compare_digit_seq(a.as_bytes(), b.as_bytes()),
+            (Item::Int(_), Some(_)) => Ordering::Greater,
+            (Item::Str(a), None) => qualifier_rank(a).cmp(&RELEASE),
+            (Item::Str(a), Some(Item::Str(b))) => qualifier_rank(a).cmp(&qualifier_rank(b)),
+            (Item::Str(_), Some(_)) => Ordering::Less,
+            (Item::List(items), None) => items
+                .iter()
+                .map(|item| item.compare(None))
+                .find(|&cmp| cmp != Ordering::Equal)
+                .unwrap_or(Ordering::Equal),
+            (Item::List(_), Some(Item::Int(_))) => Ordering::Less,
+            (Item::List(_), Some(Item::Str(_))) => Ordering::Greater,
+            (Item::List(a), Some(Item::List(b))) => compare_lists(a, b),
+        }
+    }
+}
+
+fn compare_lists(a: &[Item], b: &[Item]) -> Ordering {
+    for i in 0..a.len().max(b.len()) {
+        let cmp = match (a.get(i), b.get(i)) {
+            (Some(a), b) => a.compare(b),
+            (None, Some(b)) => b.compare(None).reverse(),
+            (None, None) => Ordering::Equal,
+        };
+        if cmp != Ordering::Equal {
+            return cmp;
+        }
+    }
+    Ordering::Equal
+}
+
+// qualifier_rank returns the position of a known qualifier, or sorts an unknown one after all
+// known qualifiers.
+fn qualifier_rank(qualifier: &str) -> (usize, &str) {
+    match QUALIFIERS.iter().position(|&q| q == qualifier) {
+        Some(i) => (i, ""),
+        None => (QUALIFIERS.len(), qualifier),
+    }
+}
+
+fn write_items(out: &mut String, items: &[Item]) {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            out.push(if matches!(item, Item::List(_)) {
+                '-'
+            } else {
+                '.'
+            });
+        }
+        match item {
+            Item::Int(digits) if digits.is_empty() => out.push('0'),
+            Item::Int(digits) => out.push_str(digits),
+            Item::Str(qualifier) => out.push_str(qualifier),
+            Item::List(items) => write_items(out, items),
+        }
+    }
+}
+
+impl From<&str> for MavenVersion {
+    fn from(s: &str) -> Self {
+        Self::new(s)
+    }
+}
+
+impl fmt::Display for MavenVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.value)
+    }
+}
+
+impl Ord for MavenVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_lists(&self.items, &other.items)
+    }
+}
+
+impl PartialOrd for MavenVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for MavenVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for MavenVersion {}
+
+/// compare compares two Maven artifact versions like `ComparableVersion` does.
+/// # Examples
+/// ```
+/// use vsort::maven;
+///
+/// fn main() {
+///     let mut versions = vec!["2.0-sp1", "2.0.RELEASE", "2.0-SNAPSHOT", "2.0-beta-1", "2.0-rc1"];
+///     versions.sort_by(|a, b| maven::compare(a, b));
+///     assert_eq!(
+///         versions,
+///         vec!["2.0-beta-1", "2.0-rc1", "2.0-SNAPSHOT", "2.0.RELEASE", "2.0-sp1"]
+///     );
+/// }
+/// ```
+pub fn compare(a: &str, b: &str) -> Ordering {
+    compare_lists(&parse(a), &parse(b))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    // These lists are lifted from
+    // https://github.com/apache/maven/blob/maven-3.8.x/maven-artifact/src/test/java/org/apache/maven/artifact/versioning/ComparableVersionTest.java
+    const VERSIONS_QUALIFIER: &[&str] = &[
+        "1-alpha2snapshot",
+        "1-alpha2",
+        "1-alpha-123",
+        "1-beta-2",
+        "1-beta123",
+        "1-m2",
+        "1-m11",
+        "1-rc",
+        "1-cr2",
+        "1-rc123",
+        "1-SNAPSHOT",
+        "1",
+        "1-sp",
+        "1-sp2",
+        "1-sp123",
+        "1-abc",
+        "1-def",
+        "1-pom-1",
+        "1-1-snapshot",
+        "1-1",
+        "1-2",
+        "1-123",
+    ];
+
+    const VERSIONS_NUMBER: &[&str] = &[
+        "2.0", "2-1", "2.0.a", "2.0.0.a", "2.0.2", "2.0.123", "2.1.0", "2.1-a", "2.1b", "2.1-c",
+        "2.1-1", "2.1.0.1", "2.2", "2.123", "11.a2", "11.a11", "11.b2", "11.b11", "11.m2",
+        "11.m11", "11", "11.a", "11b", "11c", "11m",
+    ];
+
+    #[test_case(VERSIONS_QUALIFIER; "qualifiers")]
+    #[test_case(VERSIONS_NUMBER; "numbers")]
+    fn test_versions_order(sorted: &[&str]) {
+        for (i, a) in sorted.iter().enumerate() {
+            for (j, b) in sorted.iter().enumerate() {
+                assert_eq!(compare(a, b), i.cmp(&j), "comparing {:?} and {:?}", a, b);
+            }
+        }
+    }
+
+    #[test_case("1", "1.0")]
+    #[test_case("1", "1.0.0")]
+    #[test_case("1.0", "1.0.0")]
+    #[test_case("1", "1-0")]
+    #[test_case("1", "1.0-0")]
+    #[test_case("1.0", "1.0-0")]
+    #[test_case("1a", "1-a")]
+    #[test_case("1a", "1.0-a")]
+    #[test_case("1a", "1.0.0-a")]
+    #[test_case("1.0a", "1-a")]
+    #[test_case("1.0.0a", "1-a")]
+    #[test_case("1x", "1-x")]
+    #[test_case("1x", "1.0-x")]
+    #[test_case("1x", "1.0.0-x")]
+    #[test_case("1.0x", "1-x")]
+    #[test_case("1.0.0x", "1-x")]
+    #[test_case("1ga", "1")]
+    #[test_case("1release", "1")]
+    #[test_case("1final", "1")]
+    #[test_case("1cr", "1rc")]
+    #[test_case("1a1", "1-alpha-1")]
+    #[test_case("1b2", "1-beta-2")]
+    #[test_case("1m3", "1-milestone-3")]
+    #[test_case("1X", "1x")]
+    #[test_case("1A", "1a")]
+    #[test_case("1GA", "1")]
+    #[test_case("1RELeaSE", "1")]
+    #[test_case("1FinaL", "1")]
+    #[test_case("1cR", "1rC")]
+    #[test_case("1m3", "1MILESTONE3")]
+    fn test_versions_equal(a: &str, b: &str) {
+        assert_eq!(compare(a, b), Ordering::Equal);
+        assert_eq!(MavenVersion::new(a), MavenVersion::new(b));
+        assert_eq!(
+            MavenVersion::new(a).canonical(),
+            MavenVersion::new(b).canonical()
+        );
+    }
+
+    #[test_case("1", "2")]
+    #[test_case("1.5", "2")]
+    #[test_case("1", "2.5")]
+    #[test_case("1.0", "1.1")]
+    #[test_case("1.1", "1.2")]
+    #[test_case("1.0.0", "1.1")]
+    #[test_case("1.0.1", "1.1")]
+    #[test_case("1.1", "1.2.0")]
+    #[test_case("1.0-alpha-1", "1.0")]
+    #[test_case("1.0-alpha-1", "1.0-alpha-2")]
+    #[test_case("1.0-alpha-1", "1.0-beta-1")]
+    #[test_case("1.0-beta-1", "1.0-SNAPSHOT")]
+    #[test_case("1.0-SNAPSHOT", "1.0")]
+    #[test_case("1.0-alpha-1-SNAPSHOT", "1.0-alpha-1")]
+    #[test_case("1.0", "1.0-1")]
+    #[test_case("1.0-1", "1.0-2")]
+    #[test_case("1.0.0", "1.0-1")]
+    #[test_case("2.0-1", "2.0.1")]
+    #[test_case("2.0.1-klm", "2.0.1-lmn")]
+    #[test_case("2.0.1", "2.0.1-xyz")]
+    #[test_case("2.0.1", "2.0.1-123")]
+    #[test_case("2.0.1-xyz", "2.0.1-123")]
+    // MNG-5568
+    #[test_case("6.1.0rc3", "6.1.0")]
+    #[test_case("6.1.0rc3", "6.1H.5-beta")]
+    #[test_case("6.1.0", "6.1H.5-beta")]
+    // Numbers that do not fit in 32 or 64 bits, MNG-6572
+    #[test_case("20190126.230843", "1234567890.12345")]
+    #[test_case("1234567890.12345", "123456789012345.1H.5-beta")]
+    #[test_case("123456789012345.1H.5-beta", "12345678901234567890.1H.5-beta")]
+    // MNG-6964
+    #[test_case("1-0.alpha", "1")]
+    #[test_case("1-0.beta", "1")]
+    #[test_case("1-0.alpha", "1-0.beta")]
+    fn test_versions_order_pairs(a: &str, b: &str) {
+        assert_eq!(compare(a, b), Ordering::Less);
+        assert_eq!(compare(b, a), Ordering::Greater);
+    }
+
+    #[test_case("1.0-alpha-1", "1-alpha-1")]
+    #[test_case("1.0a2", "1-alpha-2")]
+    #[test_case("2.0.RELEASE", "2")]
+    #[test_case("1.0-SNAPSHOT.007", "1-snapshot.7")]
+    #[test_case("1..1", "1.0.1")]
+    #[test_case("", "")]
+    fn test_canonical(input: &str, expected: &str) {
+        assert_eq!(MavenVersion::new(input).canonical(), expected);
+    }
+
+    #[test]
+    fn test_display_keeps_input() {
+        assert_eq!(MavenVersion::new("2.0.RELEASE").to_string(), "2.0.RELEASE");
+    }
+}
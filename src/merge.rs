@@ -0,0 +1,266 @@
+use alloc::vec::Vec;
+use core::iter::FusedIterator;
+
+use crate::{Comparator, VersionComparator};
+
+/// merge_sorted merges iterators that are each sorted with GNU version sort into one sorted
+/// iterator, like `sort -m`. The merge is stable: equal items come out in the order of the
+/// iterators that yielded them. Call `dedup` on the result to drop repeated items.
+///
+/// Items that are out of order in their own iterator stay out of order in the result.
+/// # Examples
+/// ```
+/// use vsort::merge_sorted;
+///
+/// fn main() {
+///     let mirror_a = vec!["pkg-1.0~rc1", "pkg-1.0", "pkg-10"];
+///     let mirror_b = vec!["pkg-1.0", "pkg-9"];
+///     let merged: Vec<&str> = merge_sorted([mirror_a, mirror_b]).dedup().collect();
+///     assert_eq!(merged, vec!["pkg-1.0~rc1", "pkg-1.0", "pkg-9", "pkg-10"]);
+/// }
+/// ```
+pub fn merge_sorted<I>(iters: I) -> MergeSorted<<I::Item as IntoIterator>::IntoIter, Comparator>
+where
+    I: IntoIterator,
+    I::Item: IntoIterator,
+    <I::Item as IntoIterator>::Item: AsRef<[u8]>,
+{
+    merge_sorted_with(iters, Comparator::default())
+}
+
+/// merge_sorted_with is like `merge_sorted`, but for iterators that are each sorted with the
+/// given comparator.
+/// # Examples
+/// ```
+/// use vsort::comparator::Mode;
+/// use vsort::{merge_sorted_with, VersionComparator};
+///
+/// fn main() {
+///     let merged: Vec<&str> =
+///         merge_sorted_with([vec!["1.10", "1.2"], vec!["1.9"]], Mode::Gnu.reverse()).collect();
+///     assert_eq!(merged, vec!["1.10", "1.9", "1.2"]);
+/// }
+/// ```
+pub fn merge_sorted_with<I, C>(
+    iters: I,
+    comparator: C,
+) -> MergeSorted<<I::Item as IntoIterator>::IntoIter, C>
+where
+    I: IntoIterator,
+    I::Item: IntoIterator,
+    <I::Item as IntoIterator>::Item: AsRef<[u8]>,
+    C: VersionComparator,
+{
+    let mut iters: Vec<_> = iters.into_iter().map(IntoIterator::into_iter).collect();
+    let mut heap = MergeHeap::with_capacity(iters.len());
+    for (source, iter) in iters.iter_mut().enumerate() {
+        if let Some(item) = iter.next() {
+            heap.push(item, source, &comparator);
+        }
+    }
+    MergeSorted {
+        iters,
+        heap,
+        comparator,
+        dedup: false,
+    }
+}
+
+/// MergeSorted is the iterator returned by `merge_sorted` and `merge_sorted_with`.
+pub struct MergeSorted<I: Iterator, C> {
+    iters: Vec<I>,
+    heap: MergeHeap<I::Item>,
+    comparator: C,
+    dedup: bool,
+}
+
+impl<I, C> MergeSorted<I, C>
+where
+    I: Iterator,
+    I::Item: AsRef<[u8]>,
+    C: VersionComparator,
+{
+    /// dedup yields only the first of the items that compare as equal, like `sort -m -u`.
+    pub fn dedup(mut self) -> Self {
+        self.dedup = true;
+        self
+    }
+
+    // pop removes the smallest item and replaces it with the next item of the same iterator.
+    fn pop(&mut self) -> Option<I::Item> {
+        let (item, source) = self.heap.pop(&self.comparator)?;
+        if let Some(next) = self.iters[source].next() {
+            self.heap.push(next, source, &self.comparator);
+        }
+        Some(item)
+    }
+}
+
+impl<I, C> Iterator for MergeSorted<I, C>
+where
+    I: Iterator,
+    I::Item: AsRef<[u8]>,
+    C: VersionComparator,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.pop()?;
+        if self.dedup {
+            // The iterators are sorted, so all items equal to this one are at the top of the heap.
+            while self.heap.peek().is_some_and(|next| {
+                self.comparator
+                    .compare(next.as_ref(), item.as_ref())
+                    .is_eq()
+            }) {
+                self.pop();
+            }
+        }
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iters.iter().map(Iterator::size_hint).fold(
+            (self.heap.len(), Some(self.heap.len())),
+            |(lower, upper), (iter_lower, iter_upper)| {
+                (
+                    lower.saturating_add(iter_lower),
+                    upper.zip(iter_upper).and_then(|(a, b)| a.checked_add(b)),
+                )
+            },
+        );
+        if self.dedup {
+            (lower.min(1), upper)
+        } else {
+            (lower, upper)
+        }
+    }
+}
+
+impl<I, C> FusedIterator for MergeSorted<I, C>
+where
+    I: Iterator,
+    I::Item: AsRef<[u8]>,
+    C: VersionComparator,
+{
+}
+
+// MergeHeap is a binary min-heap of the next item of each source in a merge. The comparator is
+// passed to each operation so that the heap does not have to own it. Equal items pop in the order
+// of their sources, which keeps merges stable.
+pub(crate) struct MergeHeap<T> {
+    entries: Vec<(T, usize)>,
+}
+
+impl<T: AsRef<[u8]>> MergeHeap<T> {
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub(crate) fn peek(&self) -> Option<&T> {
+        self.entries.first().map(|(item, _)| item)
+    }
+
+    pub(crate) fn push<C: VersionComparator>(&mut self, item: T, source: usize, comparator: &C) {
+        self.entries.push((item, source));
+        let mut i = self.entries.len() - 1;
+        while i > 0 {
+            let parent = (i - 1) / 2;
+            if !self.less(i, parent, comparator) {
+                break;
+            }
+            self.entries.swap(i, parent);
+            i = parent;
+        }
+    }
+
+    // pop removes the smallest item and returns it with its source.
+    pub(crate) fn pop<C: VersionComparator>(&mut self, comparator: &C) -> Option<(T, usize)> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let top = self.entries.swap_remove(0);
+        let mut i = 0;
+        loop {
+            let mut smallest = i;
+            for child in [2 * i + 1, 2 * i + 2] {
+                if child < self.entries.len() && self.less(child, smallest, comparator) {
+                    smallest = child;
+                }
+            }
+            if smallest == i {
+                break;
+            }
+            self.entries.swap(i, smallest);
+            i = smallest;
+        }
+        Some(top)
+    }
+
+    fn less<C: VersionComparator>(&self, i: usize, j: usize, comparator: &C) -> bool {
+        let (a, a_source) = &self.entries[i];
+        let (b, b_source) = &self.entries[j];
+        comparator
+            .compare(a.as_ref(), b.as_ref())
+            .then(a_source.cmp(b_source))
+            .is_lt()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+    use crate::test::GNULIB_SORTED;
+    use crate::VersionSortOptions;
+
+    #[test_case(1; "one iterator")]
+    #[test_case(3; "three iterators")]
+    #[test_case(GNULIB_SORTED.len(); "one item each")]
+    fn test_merge_sorted(count: usize) {
+        let mut iters = vec![Vec::new(); count];
+        for (i, s) in GNULIB_SORTED.iter().enumerate() {
+            iters[i % count].push(*s);
+        }
+        let merged: Vec<&str> = merge_sorted(iters).collect();
+        assert_eq!(merged, GNULIB_SORTED);
+    }
+
+    #[test_case(&[&[], &[]], &[]; "empty iterators")]
+    #[test_case(&[&["a1", "b"], &["a01", "c"]], &["a01", "a1", "b", "c"]; "byte tie break")]
+    #[test_case(&[&["b", "b"], &["a", "b"]], &["a", "b", "b", "b"]; "duplicates are kept")]
+    fn test_merge(iters: &[&[&str]], expected: &[&str]) {
+        let merged: Vec<&str> = merge_sorted(iters.iter().map(|iter| iter.iter()))
+            .copied()
+            .collect();
+        assert_eq!(merged, expected);
+    }
+
+    #[test_case(false, &["a1", "a01", "a001", "b", "b"]; "stable")]
+    #[test_case(true, &["a1", "b"]; "dedup keeps the first")]
+    fn test_equal_items(dedup: bool, expected: &[&str]) {
+        let comparator = VersionSortOptions::new().byte_tie_break(false).build();
+        let iters = [vec!["a1", "b"], vec!["a01", "a001", "b"]];
+        let merged = merge_sorted_with(iters, comparator);
+        let merged: Vec<&str> = if dedup {
+            merged.dedup().collect()
+        } else {
+            merged.collect()
+        };
+        assert_eq!(merged, expected);
+    }
+
+    #[test]
+    fn test_size_hint() {
+        let merged = merge_sorted([vec!["a", "b"], vec!["c"], vec![]]);
+        assert_eq!(merged.size_hint(), (3, Some(3)));
+        assert_eq!(merged.dedup().size_hint(), (1, Some(3)));
+    }
+}
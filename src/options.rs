@@ -0,0 +1,203 @@
+use core::cmp::Ordering;
+
+use crate::compare_bytes_with;
+
+/// VersionSortOptions selects which of the GNU version sort rules are applied. The default
+/// options apply every rule, so the resulting `Comparator` orders strings exactly like `compare`.
+/// # Examples
+/// ```
+/// use std::cmp::Ordering;
+/// use vsort::VersionSortOptions;
+///
+/// fn main() {
+///     let comparator = VersionSortOptions::new()
+///         .reverse(true)
+///         .hidden_priority(false)
+///         .build();
+///
+///     let mut file_names = vec![".config", "b 5.txt", "b 10.txt"];
+///     file_names.sort_by(|a, b| comparator.compare(a, b));
+///     assert_eq!(file_names, vec!["b 10.txt", "b 5.txt", ".config"]);
+///
+///     let ignore_case = VersionSortOptions::new().ignore_case(true).build();
+///     assert_eq!(ignore_case.compare("README-2", "readme-10"), Ordering::Less);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct VersionSortOptions {
+    pub(crate) reverse: bool,
+    pub(crate) hidden_priority: bool,
+    pub(crate) strip_extensions: bool,
+    pub(crate) ignore_case: bool,
+    pub(crate) byte_tie_break: bool,
+}
+
+impl VersionSortOptions {
+    /// new returns the options used by GNU version sort.
+    pub const fn new() -> Self {
+        Self {
+            reverse: false,
+            hidden_priority: true,
+            strip_extensions: true,
+            ignore_case: false,
+            byte_tie_break: true,
+        }
+    }
+
+    /// reverse sorts in descending order instead of ascending order. Defaults to false.
+    pub const fn reverse(mut self, reverse: bool) -> Self {
+        self.reverse = reverse;
+        self
+    }
+
+    /// hidden_priority sorts hidden files, whose names start with a period, before all other
+    /// names. When disabled, the leading period is compared like any other character. The
+    /// special names "", "." and ".." always sort first. Defaults to true.
+    pub const fn hidden_priority(mut self, hidden_priority: bool) -> Self {
+        self.hidden_priority = hidden_priority;
+        self
+    }
+
+    /// strip_extensions compares the names without their file extensions first, and only
+    /// compares the full names when those are equal. When disabled, only the full names are
+    /// compared. Defaults to true.
+    pub const fn strip_extensions(mut self, strip_extensions: bool) -> Self {
+        self.strip_extensions = strip_extensions;
+        self
+    }
+
+    /// ignore_case compares ASCII letters as if they were lowercase. Defaults to false.
+    pub const fn ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// byte_tie_break compares the plain bytes of names that are equal as versions, e.g. "a1"
+    /// and "a01", so that only identical names compare as equal. When disabled such names
+    /// compare as equal. Defaults to true.
+    pub const fn byte_tie_break(mut self, byte_tie_break: bool) -> Self {
+        self.byte_tie_break = byte_tie_break;
+        self
+    }
+
+    /// build returns a comparator that applies these options.
+    pub const fn build(self) -> Comparator {
+        Comparator { options: self }
+    }
+}
+
+impl Default for VersionSortOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Comparator compares strings using the rules selected by `VersionSortOptions`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Comparator {
+    options: VersionSortOptions,
+}
+
+impl Comparator {
+    /// options returns the options this comparator applies.
+    pub fn options(&self) -> VersionSortOptions {
+        self.options
+    }
+
+    /// compare compares two strings.
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        self.compare_bytes(a.as_bytes(), b.as_bytes())
+    }
+
+    /// compare_bytes compares two byte strings, which do not need to be valid UTF-8.
+    pub fn compare_bytes(&self, a: &[u8], b: &[u8]) -> Ordering {
+        let cmp = compare_bytes_with(a, b, &self.options);
+        if self.options.reverse {
+            return cmp.reverse();
+        }
+        cmp
+    }
+
+    /// sort will sort the given array in place using this comparator.
+    #[cfg(feature = "alloc")]
+    pub fn sort<T: AsRef<str>>(&self, arr: &mut [T]) {
+        arr.sort_by(|a, b| self.compare(a.as_ref(), b.as_ref()));
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+    use crate::compare;
+    use crate::test::GNULIB_SORTED;
+
+    #[test]
+    fn test_default_matches_compare() {
+        let comparator = VersionSortOptions::default().build();
+        for a in GNULIB_SORTED {
+            for b in GNULIB_SORTED {
+                assert_eq!(comparator.compare(a, b), compare(a, b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_reverse() {
+        let mut list = GNULIB_SORTED.to_vec();
+        VersionSortOptions::new()
+            .reverse(true)
+            .build()
+            .sort(&mut list);
+        list.reverse();
+        assert_eq!(list, GNULIB_SORTED);
+    }
+
+    #[test_case(
+      VersionSortOptions::new().hidden_priority(false),
+      vec!["b", ".3d", "a", "..", ".", ".20d"],
+      vec![".", "..", "a", "b", ".3d", ".20d"];
+      "without hidden priority"
+    )]
+    #[test_case(
+      VersionSortOptions::new().strip_extensions(false),
+      vec!["hello-8.txt", "hello-8.2.txt"],
+      vec!["hello-8.2.txt", "hello-8.txt"];
+      "without extension stripping"
+    )]
+    #[test_case(
+      VersionSortOptions::new(),
+      vec!["hello-8.2.txt", "hello-8.txt"],
+      vec!["hello-8.txt", "hello-8.2.txt"];
+      "with extension stripping"
+    )]
+    #[test_case(
+      VersionSortOptions::new().ignore_case(true),
+      vec!["b-2", "B-10", "a-1", "A-3"],
+      vec!["a-1", "A-3", "b-2", "B-10"];
+      "ignore case"
+    )]
+    #[test_case(
+      VersionSortOptions::new(),
+      vec!["b-2", "B-10", "a-1", "A-3"],
+      vec!["A-3", "B-10", "a-1", "b-2"];
+      "respect case"
+    )]
+    fn test_options(options: VersionSortOptions, original: Vec<&str>, expected: Vec<&str>) {
+        let mut list = original;
+        options.build().sort(&mut list);
+        assert_eq!(list, expected);
+    }
+
+    #[test_case("a1", "a01"; "leading zeros")]
+    #[test_case("A-1", "a-1"; "case")]
+    fn test_byte_tie_break(a: &str, b: &str) {
+        let options = VersionSortOptions::new().ignore_case(true);
+        assert_ne!(options.build().compare(a, b), Ordering::Equal);
+        assert_eq!(
+            options.byte_tie_break(false).build().compare(a, b),
+            Ordering::Equal
+        );
+    }
+}
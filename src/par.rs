@@ -0,0 +1,91 @@
+use rayon::slice::ParallelSliceMut;
+
+use crate::{compare, VersionComparator};
+
+/// par_sort will sort the given array in place using GNU version sort, splitting the work across
+/// the rayon thread pool. It orders elements exactly like `sort`.
+/// # Examples
+/// ```
+/// use vsort::par_sort;
+///
+/// fn main() {
+///     let mut file_names: Vec<String> = (0..1000).rev().map(|i| format!("pkg-{}.deb", i)).collect();
+///
+///     par_sort(&mut file_names);
+///     assert_eq!(file_names[0], "pkg-0.deb");
+///     assert_eq!(file_names[999], "pkg-999.deb");
+/// }
+/// ```
+pub fn par_sort<T: AsRef<str> + Send>(arr: &mut [T]) {
+    arr.par_sort_by(|a, b| compare(a.as_ref(), b.as_ref()));
+}
+
+/// par_sort_by_version_key is the parallel version of `sort_by_version_key`. The key function may
+/// be called from several threads at once, so it must be `Fn` and `Sync`.
+pub fn par_sort_by_version_key<T, K, F>(arr: &mut [T], f: F)
+where
+    T: Send,
+    K: AsRef<str>,
+    F: Fn(&T) -> K + Sync,
+{
+    arr.par_sort_by(|a, b| compare(f(a).as_ref(), f(b).as_ref()));
+}
+
+/// par_sort_with is the parallel version of `VersionComparator::sort`.
+/// # Examples
+/// ```
+/// use vsort::comparator::Mode;
+/// use vsort::{par_sort_with, VersionComparator};
+///
+/// fn main() {
+///     let mut versions = vec!["1.0", "1.0~rc1", "0.9"];
+///     par_sort_with(&mut versions, &Mode::Deb.reverse());
+///     assert_eq!(versions, vec!["1.0", "1.0~rc1", "0.9"]);
+/// }
+/// ```
+pub fn par_sort_with<T, C>(arr: &mut [T], comparator: &C)
+where
+    T: AsRef<[u8]> + Send,
+    C: VersionComparator + Sync,
+{
+    arr.par_sort_by(|a, b| comparator.compare(a.as_ref(), b.as_ref()));
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::comparator::Mode;
+    use crate::test::GNULIB_SORTED;
+    use crate::{sort, sort_by_version_key};
+
+    #[test]
+    fn test_par_sort_matches_sort() {
+        let mut expected: Vec<String> = (0..500)
+            .flat_map(|i| GNULIB_SORTED.iter().map(move |s| format!("{}{}", s, i % 7)))
+            .collect();
+        let mut list = expected.clone();
+        list.reverse();
+        par_sort(&mut list);
+        sort(&mut expected);
+        assert_eq!(list, expected);
+    }
+
+    #[test]
+    fn test_par_sort_by_version_key() {
+        let mut list: Vec<(usize, &str)> = GNULIB_SORTED.iter().copied().enumerate().collect();
+        list.reverse();
+        let mut expected = list.clone();
+        par_sort_by_version_key(&mut list, |(_, name)| *name);
+        sort_by_version_key(&mut expected, |(_, name)| *name);
+        assert_eq!(list, expected);
+    }
+
+    #[test]
+    fn test_par_sort_with() {
+        let comparator = Mode::Gnu.reverse();
+        let mut list = GNULIB_SORTED.to_vec();
+        par_sort_with(&mut list, &comparator);
+        list.reverse();
+        assert_eq!(list, GNULIB_SORTED);
+    }
+}
@@ -0,0 +1,595 @@
+//! Python package version ordering, as specified by PEP 440 and implemented by
+//! `packaging.version`.
+//!
+//! A version has the form `[N!]N(.N)*[{a|b|rc}N][.postN][.devN][+local]`. Versions are ordered
+//! by their epoch, their release numbers with trailing zeros ignored, and then by their pre, post
+//! and development release. A development release sorts before the release it precedes, and a
+//! version with a local label sorts after the same version without one.
+//! https://peps.python.org/pep-0440/
+//!
+//! Parsing accepts the same alternative spellings as `packaging`, e.g. `1.0-alpha_1` or
+//! `v1.0.rev2`, and normalizes them.
+use core::cmp::Ordering;
+use core::fmt;
+use core::str::FromStr;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Pep440Version is a parsed and normalized Python package version.
+///
+/// Versions that only differ in their spelling, e.g. `1.0` and `1.0.0`, are equal.
+/// # Examples
+/// ```
+/// use vsort::pep440::{Pep440Version, PreRelease};
+///
+/// fn main() {
+///     let version: Pep440Version = "1.0-Alpha_2.post3".parse().unwrap();
+///     assert_eq!(version.release(), &[1, 0]);
+///     assert_eq!(version.pre(), Some((PreRelease::Alpha, 2)));
+///     assert_eq!(version.post(), Some(3));
+///     assert_eq!(version.to_string(), "1.0a2.post3");
+///
+///     let dev: Pep440Version = "1.0.dev1".parse().unwrap();
+///     assert!(dev < version);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Pep440Version {
+    epoch: u64,
+    release: Vec<u64>,
+    pre: Option<(PreRelease, u64)>,
+    post: Option<u64>,
+    dev: Option<u64>,
+    local: Vec<LocalSegment>,
+}
+
+/// PreRelease is the phase of a pre-release, in the order the phases sort in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PreRelease {
+    /// An alpha release, spelled `a` or `alpha`.
+    Alpha,
+    /// A beta release, spelled `b` or `beta`.
+    Beta,
+    /// A release candidate, spelled `rc`, `c`, `pre` or `preview`.
+    Rc,
+}
+
+impl fmt::Display for PreRelease {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PreRelease::Alpha => "a",
+            PreRelease::Beta => "b",
+            PreRelease::Rc => "rc",
+        })
+    }
+}
+
+/// LocalSegment is one segment of a local version label. Text segments sort before numeric
+/// ones.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LocalSegment {
+    /// A segment with at least one letter, in lowercase.
+    Text(String),
+    /// A segment of digits only.
+    Number(u64),
+}
+
+impl fmt::Display for LocalSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LocalSegment::Text(s) => f.write_str(s),
+            LocalSegment::Number(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+// The spellings of each phase. Longer spellings come first, so that "preview" is not read as
+// "pre" followed by garbage.
+const PRE_LABELS: &[(&str, PreRelease)] = &[
+    ("alpha", PreRelease::Alpha),
+    ("a", PreRelease::Alpha),
+    ("beta", PreRelease::Beta),
+    ("b", PreRelease::Beta),
+    ("preview", PreRelease::Rc),
+    ("pre", PreRelease::Rc),
+    ("rc", PreRelease::Rc),
+    ("c", PreRelease::Rc),
+];
+const POST_LABELS: &[(&str, ())] = &[("post", ()), ("rev", ()), ("r", ())];
+const DEV_LABELS: &[(&str, ())] = &[("dev", ())];
+
+impl Pep440Version {
+    /// parse parses a version like `packaging.version.Version` does. Leading and trailing
+    /// whitespace and a leading `v` are ignored.
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        let s = s.strip_prefix(['v', 'V']).unwrap_or(s);
+
+        let (epoch, s) = match take_number(s)? {
+            (Some(epoch), rest) if rest.starts_with('!') => (epoch, &rest[1..]),
+            _ => (0, s),
+        };
+
+        let mut release = Vec::new();
+        let (number, mut s) = take_number(s)?;
+        release.push(number.ok_or(ParseError::InvalidRelease)?);
+        while let Some(rest) = s.strip_prefix('.') {
+            match take_number(rest)? {
+                (Some(number), rest) => {
+                    release.push(number);
+                    s = rest;
+                }
+                (None, _) => break,
+            }
+        }
+
+        let (pre, s) = match take_labeled_number(s, PRE_LABELS)? {
+            Some((phase, number, rest)) => (Some((phase, number)), rest),
+            None => (None, s),
+        };
+        // The post release can also be written as a hyphen followed by a number, e.g. "1.0-1".
+        let (post, s) = match s.strip_prefix('-').map(take_number).transpose()? {
+            Some((Some(number), rest)) => (Some(number), rest),
+            _ => match take_labeled_number(s, POST_LABELS)? {
+                Some(((), number, rest)) => (Some(number), rest),
+                None => (None, s),
+            },
+        };
+        let (dev, s) = match take_labeled_number(s, DEV_LABELS)? {
+            Some(((), number, rest)) => (Some(number), rest),
+            None => (None, s),
+        };
+
+        let (local, s) = match s.strip_prefix('+') {
+            Some(label) => (parse_local(label)?, ""),
+            None => (Vec::new(), s),
+        };
+        if let Some(c) = s.chars().next() {
+            return Err(ParseError::UnexpectedChar(c));
+        }
+
+        Ok(Self {
+            epoch,
+            release,
+            pre,
+            post,
+            dev,
+            local,
+        })
+    }
+
+    /// epoch returns the epoch, which is 0 when the version does not have one.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// release returns the release numbers, e.g. `[1, 2, 0]` for `1.2.0`.
+    pub fn release(&self) -> &[u64] {
+        &self.release
+    }
+
+    /// pre returns the phase and number of the pre-release, if the version is one.
+    pub fn pre(&self) -> Option<(PreRelease, u64)> {
+        self.pre
+    }
+
+    /// post returns the number of the post release, if the version is one.
+    pub fn post(&self) -> Option<u64> {
+        self.post
+    }
+
+    /// dev returns the number of the development release, if the version is one.
+    pub fn dev(&self) -> Option<u64> {
+        self.dev
+    }
+
+    /// local returns the segments of the local version label, which are empty when the version
+    /// does not have one.
+    pub fn local(&self) -> &[LocalSegment] {
+        &self.local
+    }
+
+    // release_key returns the release numbers without trailing zeros, since "1.0" and "1" are
+    // the same version.
+    fn release_key(&self) -> &[u64] {
+        let len = self
+            .release
+            .iter()
+            .rposition(|&n| n != 0)
+            .map_or(0, |i| i + 1);
+        &self.release[..len]
+    }
+
+    fn pre_key(&self) -> PreKey {
+        match (self.pre, self.post, self.dev) {
+            (Some((phase, number)), _, _) => PreKey::Pre(phase, number),
+            (None, None, Some(_)) => PreKey::DevOnly,
+            (None, _, _) => PreKey::Release,
+        }
+    }
+
+    // dev_key sorts a version without a development release after all of its development
+    // releases.
+    fn dev_key(&self) -> (bool, Option<u64>) {
+        (self.dev.is_none(), self.dev)
+    }
+}
+
+// PreKey ranks the pre-release of a version. A development release of a version without a pre or
+// post release, e.g. "1.0.dev1", sorts before all of its pre-releases, like "1.0a1". A version
+// without a pre-release sorts after them.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum PreKey {
+    DevOnly,
+    Pre(PreRelease, u64),
+    Release,
+}
+
+// take_number splits off the leading digits of s as a number, if there are any.
+fn take_number(s: &str) -> Result<(Option<u64>, &str), ParseError> {
+    let len = s.bytes().take_while(|c| c.is_ascii_digit()).count();
+    if len == 0 {
+        return Ok((None, s));
+    }
+    let number = s[..len].parse().map_err(|_| ParseError::NumberTooBig)?;
+    Ok((Some(number), &s[len..]))
+}
+
+// take_labeled_number splits off an optional separator, one of the given labels, another optional
+// separator and an optional number, which defaults to 0. It returns None if s does not start with
+// one of the labels.
+fn take_labeled_number<'a, T: Copy>(
+    s: &'a str,
+    labels: &[(&str, T)],
+) -> Result<Option<(T, u64, &'a str)>, ParseError> {
+    let s = strip_separator(s);
+    let found = labels.iter().find(|(label, _)| {
+        s.get(..label.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(label))
+    });
+    let (label, value) = match found {
+        Some(&(label, value)) => (label, value),
+        None => return Ok(None),
+    };
+    let (number, rest) = take_number(strip_separator(&s[label.len()..]))?;
+    Ok(Some((value, number.unwrap_or(0), rest)))
+}
+
+fn strip_separator(s: &str) -> &str {
+    s.strip_prefix(['-', '_', '.']).unwrap_or(s)
+}
+
+fn parse_local(label: &str) -> Result<Vec<LocalSegment>, ParseError> {
+    label
+        .split(['-', '_', '.'])
+        .map(|segment| {
+            if segment.is_empty() || !segment.bytes().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(ParseError::InvalidLocal);
+            }
+            if segment.bytes().all(|c| c.is_ascii_digit()) {
+                let number = segment.parse().map_err(|_| ParseError::NumberTooBig)?;
+                Ok(LocalSegment::Number(number))
+            } else {
+                Ok(LocalSegment::Text(segment.to_ascii_lowercase()))
+            }
+        })
+        .collect()
+}
+
+impl FromStr for Pep440Version {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+// Display writes the normalized form of the version.
+impl fmt::Display for Pep440Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.epoch > 0 {
+            write!(f, "{}!", self.epoch)?;
+        }
+        for (i, number) in self.release.iter().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+            write!(f, "{}", number)?;
+        }
+        if let Some((phase, number)) = self.pre {
+            write!(f, "{}{}", phase, number)?;
+        }
+        if let Some(number) = self.post {
+            write!(f, ".post{}", number)?;
+        }
+        if let Some(number) = self.dev {
+            write!(f, ".dev{}", number)?;
+        }
+        for (i, segment) in self.local.iter().enumerate() {
+            f.write_str(if i == 0 { "+" } else { "." })?;
+            write!(f, "{}", segment)?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for Pep440Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.epoch
+            .cmp(&other.epoch)
+            .then_with(|| self.release_key().cmp(other.release_key()))
+            .then_with(|| self.pre_key().cmp(&other.pre_key()))
+            .then_with(|| self.post.cmp(&other.post))
+            .then_with(|| self.dev_key().cmp(&other.dev_key()))
+            .then_with(|| self.local.cmp(&other.local))
+    }
+}
+
+impl PartialOrd for Pep440Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Like packaging, versions that only differ in their spelling, e.g. "1.0" and "1.0.0", are equal.
+impl PartialEq for Pep440Version {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Pep440Version {}
+
+/// compare compares two strings as PEP 440 versions. Strings of the form `name-version`, such
+/// as `pkg-1.0.post2`, are compared by name with GNU version sort and then by version.
+///
+/// compare is lenient: it accepts any input and never fails. Strings with a valid version sort
+/// before all other strings, and ties between them, e.g. `1.0` and `1.0.0`, are broken with GNU
+/// version sort. Strings without a valid version are ordered with GNU version sort.
+/// # Examples
+/// ```
+/// use vsort::pep440;
+///
+/// fn main() {
+///     let mut versions = vec!["1!0.9", "1.0.post2", "1.0", "1.0a1", "1.0.dev1"];
+///     versions.sort_by(|a, b| pep440::compare(a, b));
+///     assert_eq!(versions, vec!["1.0.dev1", "1.0a1", "1.0", "1.0.post2", "1!0.9"]);
+///
+///     let mut names = vec!["pkg-1.0.post2", "pkg-1.0", "pkg-1.0.dev1", "pkg-1.0a1"];
+///     names.sort_by(|a, b| pep440::compare(a, b));
+///     assert_eq!(names, vec!["pkg-1.0.dev1", "pkg-1.0a1", "pkg-1.0", "pkg-1.0.post2"]);
+/// }
+/// ```
+pub fn compare(a: &str, b: &str) -> Ordering {
+    match (split_name(a), split_name(b)) {
+        (Some((a_name, a_version)), Some((b_name, b_version))) => crate::compare(a_name, b_name)
+            .then_with(|| a_version.cmp(&b_version))
+            .then_with(|| crate::compare(a, b)),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => crate::compare(a, b),
+    }
+}
+
+// split_name splits a string like `pkg-1.0` into its name and version. The version is the longest
+// suffix that follows a `-`, starts with a digit and parses, so both parts may contain hyphens, as
+// in `my-pkg-1.0-1`. A string that is a version on its own has an empty name.
+fn split_name(s: &str) -> Option<(&str, Pep440Version)> {
+    if let Ok(version) = Pep440Version::parse(s) {
+        return Some(("", version));
+    }
+    s.match_indices('-')
+        .map(|(i, _)| (&s[..i], &s[i + 1..]))
+        .filter(|(_, version)| version.starts_with(|c: char| c.is_ascii_digit()))
+        .find_map(|(name, version)| Some((name, Pep440Version::parse(version).ok()?)))
+}
+
+/// ParseError describes why a string is not a valid PEP 440 version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The version is empty.
+    Empty,
+    /// The version does not have a release number.
+    InvalidRelease,
+    /// A number does not fit in 64 bits.
+    NumberTooBig,
+    /// The local version label has an empty segment or a character other than alphanumerics and
+    /// the separators `-_.`.
+    InvalidLocal,
+    /// The version contains a character that cannot appear at its position.
+    UnexpectedChar(char),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Empty => f.write_str("version string is empty"),
+            ParseError::InvalidRelease => f.write_str("version does not have a release number"),
+            ParseError::NumberTooBig => f.write_str("number in version is too big"),
+            ParseError::InvalidLocal => f.write_str("invalid local version label"),
+            ParseError::UnexpectedChar(c) => write!(f, "unexpected character {:?} in version", c),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    // This list is lifted from
+    // https://github.com/pypa/packaging/blob/main/tests/test_version.py
+    const PACKAGING_SORTED: &[&str] = &[
+        // Implicit epoch of 0
+        "1.0.dev456",
+        "1.0a1",
+        "1.0a2.dev456",
+        "1.0a12.dev456",
+        "1.0a12",
+        "1.0b1.dev456",
+        "1.0b2",
+        "1.0b2.post345.dev456",
+        "1.0b2.post345",
+        "1.0b2-346",
+        "1.0c1.dev456",
+        "1.0c1",
+        "1.0rc2",
+        "1.0c3",
+        "1.0",
+        "1.0.post456.dev34",
+        "1.0.post456",
+        "1.1.dev1",
+        "1.2+123abc",
+        "1.2+123abc456",
+        "1.2+abc",
+        "1.2+abc123",
+        "1.2+abc123def",
+        "1.2+1234.abc",
+        "1.2+123456",
+        "1.2.r32+123456",
+        "1.2.rev33+123456",
+        // Explicit epoch of 1
+        "1!1.0.dev456",
+        "1!1.0a1",
+        "1!1.0a2.dev456",
+        "1!1.0a12.dev456",
+        "1!1.0a12",
+        "1!1.0b1.dev456",
+        "1!1.0b2",
+        "1!1.0b2.post345.dev456",
+        "1!1.0b2.post345",
+        "1!1.0b2-346",
+        "1!1.0c1.dev456",
+        "1!1.0c1",
+        "1!1.0rc2",
+        "1!1.0c3",
+        "1!1.0",
+        "1!1.0.post456.dev34",
+        "1!1.0.post456",
+        "1!1.1.dev1",
+        "1!1.2+123abc",
+        "1!1.2+123abc456",
+        "1!1.2+abc",
+        "1!1.2+abc123",
+        "1!1.2+abc123def",
+        "1!1.2+1234.abc",
+        "1!1.2+123456",
+        "1!1.2.r32+123456",
+        "1!1.2.rev33+123456",
+    ];
+
+    #[test]
+    fn test_packaging_order() {
+        let versions: Vec<Pep440Version> = PACKAGING_SORTED
+            .iter()
+            .map(|s| s.parse().unwrap())
+            .collect();
+        for (i, a) in versions.iter().enumerate() {
+            for (j, b) in versions.iter().enumerate() {
+                assert_eq!(a.cmp(b), i.cmp(&j), "comparing {} and {}", a, b);
+            }
+        }
+
+        let mut list = PACKAGING_SORTED.to_vec();
+        list.reverse();
+        list.sort_by(|a, b| compare(a, b));
+        assert_eq!(list, PACKAGING_SORTED);
+    }
+
+    #[test_case("1.0dev", "1.0.dev0"; "implicit dev number")]
+    #[test_case("1.0-dev1", "1.0.dev1"; "hyphen before dev")]
+    #[test_case("1.0_DEV_1", "1.0.dev1"; "uppercase dev")]
+    #[test_case("1.0a", "1.0a0"; "implicit pre number")]
+    #[test_case("1.0.alpha1", "1.0a1"; "alpha")]
+    #[test_case("1.0-beta.2", "1.0b2"; "beta")]
+    #[test_case("1.0c1", "1.0rc1"; "c")]
+    #[test_case("1.0pre1", "1.0rc1"; "pre")]
+    #[test_case("1.0preview", "1.0rc0"; "preview")]
+    #[test_case("1.0RC1", "1.0rc1"; "uppercase rc")]
+    #[test_case("1.0post", "1.0.post0"; "implicit post number")]
+    #[test_case("1.0-r4", "1.0.post4"; "r")]
+    #[test_case("1.0rev4", "1.0.post4"; "rev")]
+    #[test_case("1.0-1", "1.0.post1"; "implicit post")]
+    #[test_case("1.0+ubuntu-1", "1.0+ubuntu.1"; "hyphen in local")]
+    #[test_case("1.0+UBUNTU_1", "1.0+ubuntu.1"; "uppercase local")]
+    #[test_case("1.0+007", "1.0+7"; "numeric local")]
+    #[test_case("v1.0", "1.0"; "leading v")]
+    #[test_case("  1.0\n", "1.0"; "whitespace")]
+    #[test_case("0!1.0", "1.0"; "zero epoch")]
+    #[test_case("01.02", "1.2"; "leading zeros")]
+    #[test_case("1.0a-", "1.0a0"; "trailing separator")]
+    fn test_normalize(input: &str, expected: &str) {
+        assert_eq!(Pep440Version::parse(input).unwrap().to_string(), expected);
+    }
+
+    #[test_case("", ParseError::Empty; "empty")]
+    #[test_case("french toast", ParseError::InvalidRelease; "not a version")]
+    #[test_case("1!", ParseError::InvalidRelease; "epoch only")]
+    #[test_case("1.0+", ParseError::InvalidLocal; "empty local")]
+    #[test_case("1.0+_foobar", ParseError::InvalidLocal; "local starts with separator")]
+    #[test_case("1.0+foo&asd", ParseError::InvalidLocal; "invalid local char")]
+    #[test_case("1.0+1+1", ParseError::InvalidLocal; "two local labels")]
+    #[test_case("1.0x", ParseError::UnexpectedChar('x'); "unknown label")]
+    #[test_case("1.0prev", ParseError::UnexpectedChar('v'); "misspelled label")]
+    #[test_case("1.0.dev1.post1", ParseError::UnexpectedChar('.'); "post after dev")]
+    #[test_case("1.99999999999999999999", ParseError::NumberTooBig; "number too big")]
+    fn test_parse_errors(input: &str, expected: ParseError) {
+        assert_eq!(Pep440Version::parse(input).unwrap_err(), expected);
+    }
+
+    #[test_case("1.0", "1.0.0", Ordering::Equal; "trailing zeros")]
+    #[test_case("1.0", "1.0+local", Ordering::Less; "local after release")]
+    #[test_case("1.0.dev1", "1.0a1.dev1", Ordering::Less; "dev before pre dev")]
+    #[test_case("1.0.post1.dev1", "1.0.post1", Ordering::Less; "post dev before post")]
+    #[test_case("1.0.post1.dev1", "1.0", Ordering::Greater; "post dev after release")]
+    fn test_ordering(a: &str, b: &str, expected: Ordering) {
+        let a = Pep440Version::parse(a).unwrap();
+        let b = Pep440Version::parse(b).unwrap();
+        assert_eq!(a.cmp(&b), expected);
+        assert_eq!(b.cmp(&a), expected.reverse());
+    }
+
+    #[test_case("1.0", "1.0.0", Ordering::Less; "tie break with version sort")]
+    #[test_case("1.0", "pkg-foo", Ordering::Less; "valid before invalid")]
+    #[test_case("pkg-1.0.dev1", "pkg-1.0a1", Ordering::Less; "names with dev and pre")]
+    #[test_case("pkg-1.0a1", "pkg-1.0", Ordering::Less; "names with pre")]
+    #[test_case("pkg-1.0", "pkg-1.0.post2", Ordering::Less; "names with post")]
+    #[test_case("pkg-1.0.post2", "pkg-1!0.9", Ordering::Less; "names with epoch")]
+    #[test_case("pkg-2.0", "pkg-extra-1.0", Ordering::Less; "name before version")]
+    #[test_case("my-pkg-1.0-1", "my-pkg-1.0", Ordering::Greater; "hyphens in name and version")]
+    #[test_case("pkg-1.0", "pkg-foo", Ordering::Less; "name without version")]
+    fn test_compare_lenient(a: &str, b: &str, expected: Ordering) {
+        assert_eq!(compare(a, b), expected);
+        assert_eq!(compare(b, a), expected.reverse());
+    }
+
+    #[test]
+    fn test_sort_names() {
+        let mut names = vec![
+            "pkg-1.0.post2",
+            "pkg-1!2.0",
+            "pkg-1.0",
+            "pkg-1.0.dev1",
+            "pkg-1.0a1",
+        ];
+        names.sort_by(|a, b| compare(a, b));
+        assert_eq!(
+            names,
+            vec![
+                "pkg-1.0.dev1",
+                "pkg-1.0a1",
+                "pkg-1.0",
+                "pkg-1.0.post2",
+                "pkg-1!2.0"
+            ]
+        );
+    }
+}
@@ -0,0 +1,401 @@
+//! RPM package version ordering, as implemented by `rpmvercmp`.
+//!
+//! RPM splits versions into alphabetic and numeric segments and ignores all other characters,
+//! which only act as separators. Numeric segments are newer than alphabetic ones. The tilde
+//! sorts before everything, even the end of the version, so `1.0~rc1` is older than `1.0`. The
+//! caret sorts after the end of the version but before any other segment, so `1.0^git1` is newer
+//! than `1.0` but older than `1.0.1`.
+//! https://rpm-software-management.github.io/rpm/manual/dependencies.html#versioning
+use core::cmp::Ordering;
+use core::fmt;
+
+use crate::compare_digit_seq;
+
+/// rpmvercmp compares two version or release strings like RPM's `rpmvercmp`.
+///
+/// Since separators are ignored, versions that only differ in their separators or leading zeros,
+/// e.g. "2.0" and "2_0", compare as equal.
+/// # Examples
+/// ```
+/// use std::cmp::Ordering;
+/// use vsort::rpm::rpmvercmp;
+///
+/// fn main() {
+///     let mut versions = vec!["1.0.1", "1.0^git1", "1.0", "1.0~rc1"];
+///     versions.sort_by(|a, b| rpmvercmp(a, b));
+///     assert_eq!(versions, vec!["1.0~rc1", "1.0", "1.0^git1", "1.0.1"]);
+///
+///     assert_eq!(rpmvercmp("2.0", "2_0"), Ordering::Equal);
+/// }
+/// ```
+pub fn rpmvercmp(a: &str, b: &str) -> Ordering {
+    rpmvercmp_bytes(a.as_bytes(), b.as_bytes())
+}
+
+// rpmvercmp_bytes is a port of rpmvercmp from rpm's lib/rpmvercmp.c.
+fn rpmvercmp_bytes(a: &[u8], b: &[u8]) -> Ordering {
+    // Easy comparison to see if versions are identical.
+    if a == b {
+        return Ordering::Equal;
+    }
+    let is_separator = |c: &u8| !c.is_ascii_alphanumeric() && *c != b'~' && *c != b'^';
+
+    let mut one = a;
+    let mut two = b;
+    // Loop through each version segment of one and two and compare them.
+    while !one.is_empty() || !two.is_empty() {
+        one = &one[one.iter().take_while(|c| is_separator(c)).count()..];
+        two = &two[two.iter().take_while(|c| is_separator(c)).count()..];
+
+        // Handle the tilde separator, it sorts before everything else.
+        match (one.first(), two.first()) {
+            (Some(b'~'), Some(b'~')) => {
+                one = &one[1..];
+                two = &two[1..];
+                continue;
+            }
+            (Some(b'~'), _) => return Ordering::Less,
+            (_, Some(b'~')) => return Ordering::Greater,
+            _ => {}
+        }
+
+        // Handle the caret separator. The concept is the same as the tilde, except that if one of
+        // the strings ends (the base version), the other is considered as the newer version.
+        match (one.first(), two.first()) {
+            (Some(b'^'), Some(b'^')) => {
+                one = &one[1..];
+                two = &two[1..];
+                continue;
+            }
+            (None, Some(b'^')) => return Ordering::Less,
+            (Some(b'^'), None) => return Ordering::Greater,
+            (Some(b'^'), _) => return Ordering::Less,
+            (_, Some(b'^')) => return Ordering::Greater,
+            _ => {}
+        }
+
+        // If we ran to the end of either, we are finished with the loop.
+        if one.is_empty() || two.is_empty() {
+            break;
+        }
+
+        // Grab the first completely alpha or completely numeric segment of each.
+        let is_num = one[0].is_ascii_digit();
+        let in_segment = |c: &u8| {
+            if is_num {
+                c.is_ascii_digit()
+            } else {
+                c.is_ascii_alphabetic()
+            }
+        };
+        let one_len = one.iter().take_while(|c| in_segment(c)).count();
+        let two_len = two.iter().take_while(|c| in_segment(c)).count();
+        let (one_segment, one_rest) = one.split_at(one_len);
+        let (two_segment, two_rest) = two.split_at(two_len);
+
+        // The segments are of different types: one numeric, the other alpha. Numeric segments
+        // are always newer than alpha segments.
+        if two_segment.is_empty() {
+            return if is_num {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+        }
+
+        // Numbers are compared by value, which ignores leading zeros and works for numbers of any
+        // length. Alpha segments are compared like strcmp does.
+        let cmp = if is_num {
+            compare_digit_seq(one_segment, two_segment)
+        } else {
+            one_segment.cmp(two_segment)
+        };
+        if cmp != Ordering::Equal {
+            return cmp;
+        }
+
+        one = one_rest;
+        two = two_rest;
+    }
+
+    // This catches the case where all numeric and alpha segments have compared identically but
+    // the segment separating characters were different. Otherwise whichever version still has
+    // characters left over wins.
+    match (one.is_empty(), two.is_empty()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, _) => Ordering::Greater,
+    }
+}
+
+/// Evr is an RPM `epoch:version-release` string, split into its parts.
+///
+/// Versions are ordered by their epoch first, then by their version and finally by their
+/// release, all compared with `rpmvercmp`. A missing epoch counts as 0. A missing release sorts
+/// before any release, so `1.0` is older than `1.0-1`.
+/// # Examples
+/// ```
+/// use vsort::rpm::Evr;
+///
+/// fn main() {
+///     let evr = Evr::parse("1:2.30-5.fc38");
+///     assert_eq!(evr.epoch(), Some("1"));
+///     assert_eq!(evr.version(), "2.30");
+///     assert_eq!(evr.release(), Some("5.fc38"));
+///
+///     assert!(Evr::parse("2.30-5.fc38") < evr);
+///     assert!(Evr::parse("1:2.30~rc1-1.fc38") < evr);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Evr<'a> {
+    epoch: Option<&'a str>,
+    version: &'a str,
+    release: Option<&'a str>,
+}
+
+impl<'a> Evr<'a> {
+    /// parse splits the given string like RPM's `parseEVR`: the epoch is the run of leading
+    /// digits terminated by a colon, and the release follows the last hyphen. Parsing never
+    /// fails, since RPM accepts any string.
+    pub fn parse(s: &'a str) -> Self {
+        let digits = s.bytes().take_while(|c| c.is_ascii_digit()).count();
+        let (epoch, rest) = match s[digits..].strip_prefix(':') {
+            // An empty epoch, as in ":1.0", counts as 0.
+            Some(rest) if digits == 0 => (Some("0"), rest),
+            Some(rest) => (Some(&s[..digits]), rest),
+            None => (None, s),
+        };
+        let (version, release) = match rest.rsplit_once('-') {
+            Some((version, release)) => (version, Some(release)),
+            None => (rest, None),
+        };
+        Self {
+            epoch,
+            version,
+            release,
+        }
+    }
+
+    /// epoch returns the epoch, if the string has one.
+    pub fn epoch(&self) -> Option<&'a str> {
+        self.epoch
+    }
+
+    /// version returns the version.
+    pub fn version(&self) -> &'a str {
+        self.version
+    }
+
+    /// release returns the release, if the string has one.
+    pub fn release(&self) -> Option<&'a str> {
+        self.release
+    }
+}
+
+impl<'a> From<&'a str> for Evr<'a> {
+    fn from(s: &'a str) -> Self {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Evr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(epoch) = self.epoch {
+            write!(f, "{}:", epoch)?;
+        }
+        f.write_str(self.version)?;
+        if let Some(release) = self.release {
+            write!(f, "-{}", release)?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for Evr<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        rpmvercmp(self.epoch.unwrap_or("0"), other.epoch.unwrap_or("0"))
+            .then_with(|| rpmvercmp(self.version, other.version))
+            .then_with(|| match (self.release, other.release) {
+                (Some(a), Some(b)) => rpmvercmp(a, b),
+                (a, b) => a.is_some().cmp(&b.is_some()),
+            })
+    }
+}
+
+impl PartialOrd for Evr<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Versions that only differ in their separators or leading zeros are equal, like in RPM.
+impl PartialEq for Evr<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Evr<'_> {}
+
+/// compare parses both strings as `Evr`s and compares them.
+/// # Examples
+/// ```
+/// use vsort::rpm;
+///
+/// fn main() {
+///     let mut versions = vec!["1:1.0-1", "1.0-2.fc38", "1.0^git1-1", "1.0-10.fc38"];
+///     versions.sort_by(|a, b| rpm::compare(a, b));
+///     assert_eq!(versions, vec!["1.0-2.fc38", "1.0-10.fc38", "1.0^git1-1", "1:1.0-1"]);
+/// }
+/// ```
+pub fn compare(a: &str, b: &str) -> Ordering {
+    Evr::parse(a).cmp(&Evr::parse(b))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    // These tests are lifted from
+    // https://github.com/rpm-software-management/rpm/blob/master/tests/rpmvercmp.at
+    #[test_case("1.0", "1.0", Ordering::Equal)]
+    #[test_case("1.0", "2.0", Ordering::Less)]
+    #[test_case("2.0", "1.0", Ordering::Greater)]
+    #[test_case("2.0.1", "2.0.1", Ordering::Equal)]
+    #[test_case("2.0", "2.0.1", Ordering::Less)]
+    #[test_case("2.0.1", "2.0", Ordering::Greater)]
+    #[test_case("2.0.1a", "2.0.1a", Ordering::Equal)]
+    #[test_case("2.0.1a", "2.0.1", Ordering::Greater)]
+    #[test_case("2.0.1", "2.0.1a", Ordering::Less)]
+    #[test_case("5.5p1", "5.5p1", Ordering::Equal)]
+    #[test_case("5.5p1", "5.5p2", Ordering::Less)]
+    #[test_case("5.5p2", "5.5p1", Ordering::Greater)]
+    #[test_case("5.5p10", "5.5p10", Ordering::Equal)]
+    #[test_case("5.5p1", "5.5p10", Ordering::Less)]
+    #[test_case("5.5p10", "5.5p1", Ordering::Greater)]
+    #[test_case("10xyz", "10.1xyz", Ordering::Less)]
+    #[test_case("10.1xyz", "10xyz", Ordering::Greater)]
+    #[test_case("xyz10", "xyz10", Ordering::Equal)]
+    #[test_case("xyz10", "xyz10.1", Ordering::Less)]
+    #[test_case("xyz10.1", "xyz10", Ordering::Greater)]
+    #[test_case("xyz.4", "xyz.4", Ordering::Equal)]
+    #[test_case("xyz.4", "8", Ordering::Less)]
+    #[test_case("8", "xyz.4", Ordering::Greater)]
+    #[test_case("xyz.4", "2", Ordering::Less)]
+    #[test_case("2", "xyz.4", Ordering::Greater)]
+    #[test_case("5.5p2", "5.6p1", Ordering::Less)]
+    #[test_case("5.6p1", "5.5p2", Ordering::Greater)]
+    #[test_case("5.6p1", "6.5p1", Ordering::Less)]
+    #[test_case("6.5p1", "5.6p1", Ordering::Greater)]
+    #[test_case("6.0.rc1", "6.0", Ordering::Greater)]
+    #[test_case("6.0", "6.0.rc1", Ordering::Less)]
+    #[test_case("10b2", "10a1", Ordering::Greater)]
+    #[test_case("10a2", "10b2", Ordering::Less)]
+    #[test_case("1.0aa", "1.0aa", Ordering::Equal)]
+    #[test_case("1.0a", "1.0aa", Ordering::Less)]
+    #[test_case("1.0aa", "1.0a", Ordering::Greater)]
+    #[test_case("10.0001", "10.0001", Ordering::Equal)]
+    #[test_case("10.0001", "10.1", Ordering::Equal)]
+    #[test_case("10.1", "10.0001", Ordering::Equal)]
+    #[test_case("10.0001", "10.0039", Ordering::Less)]
+    #[test_case("10.0039", "10.0001", Ordering::Greater)]
+    #[test_case("4.999.9", "5.0", Ordering::Less)]
+    #[test_case("5.0", "4.999.9", Ordering::Greater)]
+    #[test_case("20101121", "20101121", Ordering::Equal)]
+    #[test_case("20101121", "20101122", Ordering::Less)]
+    #[test_case("20101122", "20101121", Ordering::Greater)]
+    #[test_case("2_0", "2_0", Ordering::Equal)]
+    #[test_case("2.0", "2_0", Ordering::Equal)]
+    #[test_case("2_0", "2.0", Ordering::Equal)]
+    // RhBug:178798 case
+    #[test_case("a", "a", Ordering::Equal)]
+    #[test_case("a+", "a+", Ordering::Equal)]
+    #[test_case("a+", "a_", Ordering::Equal)]
+    #[test_case("a_", "a+", Ordering::Equal)]
+    #[test_case("+a", "+a", Ordering::Equal)]
+    #[test_case("+a", "_a", Ordering::Equal)]
+    #[test_case("_a", "+a", Ordering::Equal)]
+    #[test_case("+_", "+_", Ordering::Equal)]
+    #[test_case("_+", "+_", Ordering::Equal)]
+    #[test_case("_+", "_", Ordering::Equal)]
+    #[test_case("+", "_", Ordering::Equal)]
+    #[test_case("_", "+", Ordering::Equal)]
+    // Basic testcases for tilde sorting
+    #[test_case("1.0~rc1", "1.0~rc1", Ordering::Equal)]
+    #[test_case("1.0~rc1", "1.0", Ordering::Less)]
+    #[test_case("1.0", "1.0~rc1", Ordering::Greater)]
+    #[test_case("1.0~rc1", "1.0~rc2", Ordering::Less)]
+    #[test_case("1.0~rc2", "1.0~rc1", Ordering::Greater)]
+    #[test_case("1.0~rc1~git123", "1.0~rc1~git123", Ordering::Equal)]
+    #[test_case("1.0~rc1~git123", "1.0~rc1", Ordering::Less)]
+    #[test_case("1.0~rc1", "1.0~rc1~git123", Ordering::Greater)]
+    // Basic testcases for caret sorting
+    #[test_case("1.0^", "1.0^", Ordering::Equal)]
+    #[test_case("1.0^", "1.0", Ordering::Greater)]
+    #[test_case("1.0", "1.0^", Ordering::Less)]
+    #[test_case("1.0^git1", "1.0^git1", Ordering::Equal)]
+    #[test_case("1.0^git1", "1.0", Ordering::Greater)]
+    #[test_case("1.0", "1.0^git1", Ordering::Less)]
+    #[test_case("1.0^git1", "1.0^git2", Ordering::Less)]
+    #[test_case("1.0^git2", "1.0^git1", Ordering::Greater)]
+    #[test_case("1.0^git1", "1.01", Ordering::Less)]
+    #[test_case("1.01", "1.0^git1", Ordering::Greater)]
+    #[test_case("1.0^20160101", "1.0^20160101", Ordering::Equal)]
+    #[test_case("1.0^20160101", "1.0.1", Ordering::Less)]
+    #[test_case("1.0.1", "1.0^20160101", Ordering::Greater)]
+    #[test_case("1.0^20160101^git1", "1.0^20160101^git1", Ordering::Equal)]
+    #[test_case("1.0^20160102", "1.0^20160101^git1", Ordering::Greater)]
+    #[test_case("1.0^20160101^git1", "1.0^20160102", Ordering::Less)]
+    // Basic testcases for tilde and caret sorting
+    #[test_case("1.0~rc1^git1", "1.0~rc1^git1", Ordering::Equal)]
+    #[test_case("1.0~rc1^git1", "1.0~rc1", Ordering::Greater)]
+    #[test_case("1.0~rc1", "1.0~rc1^git1", Ordering::Less)]
+    #[test_case("1.0^git1~pre", "1.0^git1~pre", Ordering::Equal)]
+    #[test_case("1.0^git1", "1.0^git1~pre", Ordering::Greater)]
+    #[test_case("1.0^git1~pre", "1.0^git1", Ordering::Less)]
+    fn test_rpmvercmp(a: &str, b: &str, expected: Ordering) {
+        assert_eq!(rpmvercmp(a, b), expected);
+    }
+
+    #[test]
+    fn test_long_numbers() {
+        assert_eq!(
+            rpmvercmp("1.18446744073709551616", "1.18446744073709551615"),
+            Ordering::Greater
+        );
+    }
+
+    #[test_case("1.0", None, "1.0", None; "version only")]
+    #[test_case("1.0-1.fc38", None, "1.0", Some("1.fc38"); "version and release")]
+    #[test_case("2:1.0-1", Some("2"), "1.0", Some("1"); "epoch")]
+    #[test_case(":1.0", Some("0"), "1.0", None; "empty epoch")]
+    #[test_case("1.0-rc1-2", None, "1.0-rc1", Some("2"); "release starts at last hyphen")]
+    #[test_case("a:1.0", None, "a:1.0", None; "epoch must be digits")]
+    fn test_parse(input: &str, epoch: Option<&str>, version: &str, release: Option<&str>) {
+        let evr = Evr::parse(input);
+        assert_eq!(evr.epoch(), epoch);
+        assert_eq!(evr.version(), version);
+        assert_eq!(evr.release(), release);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Evr::parse("2:1.0-1.fc38").to_string(), "2:1.0-1.fc38");
+        assert_eq!(Evr::parse("1.0").to_string(), "1.0");
+        assert_eq!(Evr::parse(":1.0").to_string(), "0:1.0");
+    }
+
+    #[test_case("1.0-1", "0:1.0-1", Ordering::Equal; "missing epoch is zero")]
+    #[test_case("1:1.0-1", "2.0-1", Ordering::Greater; "epoch beats version")]
+    #[test_case("10:1.0", "9:2.0", Ordering::Greater; "epochs are numbers")]
+    #[test_case("1.0-1", "1.0-2", Ordering::Less; "release")]
+    #[test_case("1.0", "1.0-1", Ordering::Less; "missing release sorts first")]
+    #[test_case("1.0~rc1-5", "1.0-1", Ordering::Less; "tilde in version")]
+    fn test_compare(a: &str, b: &str, expected: Ordering) {
+        assert_eq!(compare(a, b), expected);
+        assert_eq!(compare(b, a), expected.reverse());
+    }
+}
@@ -0,0 +1,412 @@
+//! Semantic Versioning 2.0.0 precedence.
+//!
+//! A semantic version has the form `MAJOR.MINOR.PATCH[-PRERELEASE][+BUILD]`. Versions are ordered
+//! by their major, minor and patch numbers, and a pre-release sorts before the release it
+//! precedes. Pre-release identifiers are compared one by one: numeric identifiers by their value,
+//! other identifiers in ASCII order, and numeric identifiers sort before the others. Build
+//! metadata does not take part in precedence.
+//! https://semver.org/spec/v2.0.0.html
+use core::cmp::Ordering;
+use core::fmt;
+use core::str::FromStr;
+
+use alloc::string::{String, ToString};
+
+use crate::compare_digit_seq;
+
+/// SemVer is a parsed semantic version.
+///
+/// Since build metadata is ignored for precedence, versions that only differ in their build
+/// metadata are equal.
+/// # Examples
+/// ```
+/// use vsort::semver::SemVer;
+///
+/// fn main() {
+///     let version: SemVer = "1.0.0-rc.1+build.5".parse().unwrap();
+///     assert_eq!(version.major(), 1);
+///     assert_eq!(version.pre(), "rc.1");
+///     assert_eq!(version.build(), "build.5");
+///
+///     let release: SemVer = "1.0.0".parse().unwrap();
+///     assert!(version < release);
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct SemVer {
+    major: u64,
+    minor: u64,
+    patch: u64,
+    pre: String,
+    build: String,
+}
+
+impl SemVer {
+    /// parse parses a version that follows the Semantic Versioning 2.0.0 grammar exactly. No
+    /// leading `v` or whitespace is accepted.
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        let parts = Parts::parse(s)?;
+        Ok(Self {
+            major: parts.major,
+            minor: parts.minor,
+            patch: parts.patch,
+            pre: parts.pre.to_string(),
+            build: parts.build.to_string(),
+        })
+    }
+
+    /// major returns the major version.
+    pub fn major(&self) -> u64 {
+        self.major
+    }
+
+    /// minor returns the minor version.
+    pub fn minor(&self) -> u64 {
+        self.minor
+    }
+
+    /// patch returns the patch version.
+    pub fn patch(&self) -> u64 {
+        self.patch
+    }
+
+    /// pre returns the pre-release identifiers without the leading hyphen. It is empty for
+    /// releases.
+    pub fn pre(&self) -> &str {
+        &self.pre
+    }
+
+    /// build returns the build metadata without the leading plus sign. It is empty when the
+    /// version does not have any.
+    pub fn build(&self) -> &str {
+        &self.build
+    }
+
+    fn as_parts(&self) -> Parts<'_> {
+        Parts {
+            major: self.major,
+            minor: self.minor,
+            patch: self.patch,
+            pre: &self.pre,
+            build: &self.build,
+        }
+    }
+}
+
+impl FromStr for SemVer {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for SemVer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if !self.pre.is_empty() {
+            write!(f, "-{}", self.pre)?;
+        }
+        if !self.build.is_empty() {
+            write!(f, "+{}", self.build)?;
+        }
+        Ok(())
+    }
+}
+
+impl Ord for SemVer {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_parts().precedence(&other.as_parts())
+    }
+}
+
+impl PartialOrd for SemVer {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Build metadata is ignored, so "1.0.0+a" and "1.0.0+b" are equal.
+impl PartialEq for SemVer {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for SemVer {}
+
+// Parts is a validated version that borrows from the input, so that compare does not need to
+// allocate.
+struct Parts<'a> {
+    major: u64,
+    minor: u64,
+    patch: u64,
+    pre: &'a str,
+    build: &'a str,
+}
+
+impl<'a> Parts<'a> {
+    fn parse(s: &'a str) -> Result<Self, ParseError> {
+        if s.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        // The version core cannot contain a hyphen or plus sign, but the pre-release can contain
+        // hyphens.
+        let (rest, build) = match s.split_once('+') {
+            Some((rest, build)) => (rest, Some(build)),
+            None => (s, None),
+        };
+        let (core, pre) = match rest.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (rest, None),
+        };
+
+        let mut numbers = core.split('.');
+        let mut next_number = || numbers.next().ok_or(ParseError::InvalidVersionCore);
+        let major = parse_number(next_number()?)?;
+        let minor = parse_number(next_number()?)?;
+        let patch = parse_number(next_number()?)?;
+        if numbers.next().is_some() {
+            return Err(ParseError::InvalidVersionCore);
+        }
+
+        if let Some(pre) = pre {
+            for identifier in pre.split('.') {
+                validate_identifier(identifier)?;
+                if is_numeric(identifier) && identifier.len() > 1 && identifier.starts_with('0') {
+                    return Err(ParseError::LeadingZero);
+                }
+            }
+        }
+        // Unlike pre-release identifiers, build identifiers may have leading zeros.
+        if let Some(build) = build {
+            for identifier in build.split('.') {
+                validate_identifier(identifier)?;
+            }
+        }
+
+        Ok(Self {
+            major,
+            minor,
+            patch,
+            pre: pre.unwrap_or(""),
+            build: build.unwrap_or(""),
+        })
+    }
+
+    fn precedence(&self, other: &Self) -> Ordering {
+        self.major
+            .cmp(&other.major)
+            .then(self.minor.cmp(&other.minor))
+            .then(self.patch.cmp(&other.patch))
+            .then_with(|| compare_prerelease(self.pre, other.pre))
+    }
+}
+
+fn parse_number(s: &str) -> Result<u64, ParseError> {
+    if !is_numeric(s) {
+        return Err(ParseError::InvalidVersionCore);
+    }
+    if s.len() > 1 && s.starts_with('0') {
+        return Err(ParseError::LeadingZero);
+    }
+    s.parse().map_err(|_| ParseError::NumberTooBig)
+}
+
+pub(crate) fn validate_identifier(identifier: &str) -> Result<(), ParseError> {
+    if identifier.is_empty() {
+        return Err(ParseError::EmptyIdentifier);
+    }
+    match identifier
+        .chars()
+        .find(|&c| !(c.is_ascii_alphanumeric() || c == '-'))
+    {
+        Some(c) => Err(ParseError::InvalidChar(c)),
+        None => Ok(()),
+    }
+}
+
+pub(crate) fn is_numeric(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|c| c.is_ascii_digit())
+}
+
+// compare_prerelease compares two dot separated lists of pre-release identifiers. An empty list
+// means the version is a release, which sorts after all of its pre-releases.
+pub(crate) fn compare_prerelease(a: &str, b: &str) -> Ordering {
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return Ordering::Greater,
+        (false, true) => return Ordering::Less,
+        (false, false) => {}
+    }
+    let mut a_identifiers = a.split('.');
+    let mut b_identifiers = b.split('.');
+    loop {
+        let (a, b) = match (a_identifiers.next(), b_identifiers.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => (a, b),
+        };
+        let cmp = match (is_numeric(a), is_numeric(b)) {
+            (true, true) => compare_digit_seq(a.as_bytes(), b.as_bytes()),
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => a.cmp(b),
+        };
+        if cmp != Ordering::Equal {
+            return cmp;
+        }
+    }
+}
+
+/// compare compares two strings by semantic version precedence.
+///
+/// compare is lenient: it accepts any input and never fails. Valid semantic versions sort before
+/// all other strings, and ties between them, e.g. versions that only differ in their build
+/// metadata, are broken with GNU version sort. Strings that are not valid semantic versions are
+/// ordered with GNU version sort. Use `SemVer::parse` to reject invalid versions instead.
+/// # Examples
+/// ```
+/// use vsort::semver;
+///
+/// fn main() {
+///     let mut versions = vec!["1.0.0", "1.0.0+build", "1.0.0-rc.1", "1.0.0-alpha.1", "1.0.0-alpha"];
+///     versions.sort_by(|a, b| semver::compare(a, b));
+///     assert_eq!(
+///         versions,
+///         vec!["1.0.0-alpha", "1.0.0-alpha.1", "1.0.0-rc.1", "1.0.0", "1.0.0+build"]
+///     );
+/// }
+/// ```
+pub fn compare(a: &str, b: &str) -> Ordering {
+    match (Parts::parse(a), Parts::parse(b)) {
+        (Ok(a_parts), Ok(b_parts)) => a_parts
+            .precedence(&b_parts)
+            .then_with(|| crate::compare(a, b)),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => crate::compare(a, b),
+    }
+}
+
+/// ParseError describes why a string is not a valid semantic version.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The version is empty.
+    Empty,
+    /// The version does not start with three numbers separated by periods.
+    InvalidVersionCore,
+    /// A number or numeric pre-release identifier has a leading zero.
+    LeadingZero,
+    /// A number does not fit in 64 bits.
+    NumberTooBig,
+    /// A pre-release or build identifier is empty.
+    EmptyIdentifier,
+    /// A pre-release or build identifier contains a character other than alphanumerics and `-`.
+    InvalidChar(char),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Empty => f.write_str("version string is empty"),
+            ParseError::InvalidVersionCore => {
+                f.write_str("version does not start with MAJOR.MINOR.PATCH")
+            }
+            ParseError::LeadingZero => f.write_str("number in version has a leading zero"),
+            ParseError::NumberTooBig => f.write_str("number in version is too big"),
+            ParseError::EmptyIdentifier => f.write_str("identifier in version is empty"),
+            ParseError::InvalidChar(c) => write!(f, "invalid character {:?} in identifier", c),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    // The precedence example from https://semver.org/spec/v2.0.0.html#spec-item-11
+    const SPEC_SORTED: &[&str] = &[
+        "1.0.0-alpha",
+        "1.0.0-alpha.1",
+        "1.0.0-alpha.beta",
+        "1.0.0-beta",
+        "1.0.0-beta.2",
+        "1.0.0-beta.11",
+        "1.0.0-rc.1",
+        "1.0.0",
+        "2.0.0",
+        "2.1.0",
+        "2.1.1",
+    ];
+
+    #[test]
+    fn test_spec_order() {
+        for (i, a) in SPEC_SORTED.iter().enumerate() {
+            for (j, b) in SPEC_SORTED.iter().enumerate() {
+                let expected = i.cmp(&j);
+                assert_eq!(compare(a, b), expected, "comparing {:?} and {:?}", a, b);
+                let a = SemVer::parse(a).unwrap();
+                let b = SemVer::parse(b).unwrap();
+                assert_eq!(a.cmp(&b), expected);
+            }
+        }
+    }
+
+    #[test_case("1.0.0+a", "1.0.0+b", Ordering::Equal; "build metadata is ignored")]
+    #[test_case("1.0.0-alpha+b", "1.0.0-alpha", Ordering::Equal; "build metadata after pre-release")]
+    #[test_case("1.0.0-1", "1.0.0-a", Ordering::Less; "numeric before alphanumeric")]
+    #[test_case("1.0.0-a-1", "1.0.0-a", Ordering::Greater; "hyphen in identifier")]
+    #[test_case("1.0.0-A", "1.0.0-a", Ordering::Less; "ascii order")]
+    #[test_case("1.0.0-99999999999999999999", "1.0.0-100000000000000000000", Ordering::Less; "numbers above u64 max")]
+    #[test_case("1.2.3", "1.10.0", Ordering::Less; "numbers")]
+    fn test_precedence(a: &str, b: &str, expected: Ordering) {
+        let a = SemVer::parse(a).unwrap();
+        let b = SemVer::parse(b).unwrap();
+        assert_eq!(a.cmp(&b), expected);
+        assert_eq!(b.cmp(&a), expected.reverse());
+    }
+
+    #[test_case("", ParseError::Empty; "empty")]
+    #[test_case("1", ParseError::InvalidVersionCore; "major only")]
+    #[test_case("1.2", ParseError::InvalidVersionCore; "no patch")]
+    #[test_case("1.2.3.4", ParseError::InvalidVersionCore; "four numbers")]
+    #[test_case("v1.2.3", ParseError::InvalidVersionCore; "leading v")]
+    #[test_case("1.2.x", ParseError::InvalidVersionCore; "patch not a number")]
+    #[test_case("1..3", ParseError::InvalidVersionCore; "empty minor")]
+    #[test_case("01.1.1", ParseError::LeadingZero; "major with leading zero")]
+    #[test_case("1.2.3-0123", ParseError::LeadingZero; "pre-release with leading zero")]
+    #[test_case("18446744073709551616.0.0", ParseError::NumberTooBig; "major too big")]
+    #[test_case("1.2.3-", ParseError::EmptyIdentifier; "empty pre-release")]
+    #[test_case("1.0.0-alpha..1", ParseError::EmptyIdentifier; "empty pre-release identifier")]
+    #[test_case("1.1.2+.123", ParseError::EmptyIdentifier; "empty build identifier")]
+    #[test_case("1.2.3-+", ParseError::EmptyIdentifier; "empty pre-release and build")]
+    #[test_case("1.2.3-alpha_beta", ParseError::InvalidChar('_'); "underscore")]
+    #[test_case("1.2.3+build+1", ParseError::InvalidChar('+'); "second plus sign")]
+    fn test_parse_errors(input: &str, expected: ParseError) {
+        assert_eq!(SemVer::parse(input).unwrap_err(), expected);
+    }
+
+    #[test_case("0.0.4"; "zeros")]
+    #[test_case("1.0.0-0.3.7"; "numeric pre-release")]
+    #[test_case("1.0.0-x-y-z.--"; "hyphens")]
+    #[test_case("1.0.0+0.build.1-rc.10000aaa-kk-0.1"; "build with leading zeros")]
+    #[test_case("1.0.0-alpha+001"; "pre-release and build")]
+    fn test_display(input: &str) {
+        assert_eq!(SemVer::parse(input).unwrap().to_string(), input);
+    }
+
+    #[test_case("1.0.0", "v1.0.0", Ordering::Less; "valid before invalid")]
+    #[test_case("2.0", "1.0", Ordering::Greater; "invalid use version sort")]
+    #[test_case("1.0.0", "1.0.0+build", Ordering::Less; "tie break with version sort")]
+    fn test_compare_lenient(a: &str, b: &str, expected: Ordering) {
+        assert_eq!(compare(a, b), expected);
+        assert_eq!(compare(b, a), expected.reverse());
+    }
+}
@@ -0,0 +1,195 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{digit_seq, non_digit_seq, split_ext, strip_leading_zeros};
+
+// Every name starts with a byte that ranks its kind, following the priority given by the spec:
+// the special names ("", ".", "..") come first, then hidden files, then everything else.
+const EMPTY: u8 = 0x00;
+const DOT: u8 = 0x01;
+const DOT_DOT: u8 = 0x02;
+const HIDDEN: u8 = 0x03;
+const REGULAR: u8 = 0x04;
+
+// Non-digit characters are mapped to weights that follow the rules of VersionSortChar. The end
+// of a non-digit sequence gets its own weight so that a tilde sorts before it and everything
+// else sorts after it.
+const TILDE_WEIGHT: u8 = 0x01;
+const END_WEIGHT: u8 = 0x02;
+
+// The end of a sequence of segments compares like an infinite run of empty segments, i.e. an
+// empty non-digit sequence followed by a zero. The trailing END_WEIGHT makes it sort after a
+// leading zero followed by a tilde, and before a leading zero followed by anything else.
+const SEQUENCE_END: [u8; 3] = [END_WEIGHT, 0x00, END_WEIGHT];
+
+const WEIGHTS: [u8; 256] = weights();
+
+// weights builds the weight of every byte that can appear in a non-digit sequence: the tilde
+// first, then ASCII letters, then all other bytes. Letters and other bytes keep their relative
+// byte order. Digits never appear in non-digit sequences, so they do not get a weight.
+const fn weights() -> [u8; 256] {
+    let mut weights = [0; 256];
+    weights[b'~' as usize] = TILDE_WEIGHT;
+    let mut next = END_WEIGHT + 1;
+    let mut c = 0;
+    while c < 256 {
+        if (c as u8).is_ascii_alphabetic() {
+            weights[c] = next;
+            next += 1;
+        }
+        c += 1;
+    }
+    let mut c = 0;
+    while c < 256 {
+        let byte = c as u8;
+        if !byte.is_ascii_alphabetic() && !byte.is_ascii_digit() && byte != b'~' {
+            weights[c] = next;
+            next += 1;
+        }
+        c += 1;
+    }
+    weights
+}
+
+/// sort_key encodes the given string into bytes that sort in GNU version order. Comparing two
+/// keys with a plain byte comparison (e.g. `memcmp`, or the default ordering of a database index)
+/// gives the same result as calling `compare` on the original strings.
+/// # Examples
+/// ```
+/// use vsort::{compare, sort_key};
+///
+/// fn main() {
+///     let (a, b) = ("b 5.txt", "b 10.txt");
+///     assert_eq!(sort_key(a).cmp(&sort_key(b)), compare(a, b));
+/// }
+/// ```
+pub fn sort_key(s: &str) -> Vec<u8> {
+    sort_key_bytes(s.as_bytes())
+}
+
+/// sort_key_bytes is like `sort_key`, but for names that do not need to be valid UTF-8. The keys
+/// order the same way `compare_bytes` does.
+pub fn sort_key_bytes(s: &[u8]) -> Vec<u8> {
+    let body = match s {
+        b"" => return vec![EMPTY],
+        b"." => return vec![DOT],
+        b".." => return vec![DOT_DOT],
+        s if s.starts_with(b".") => &s[1..],
+        s => s,
+    };
+    let mut key = Vec::with_capacity(3 * body.len() + 16);
+    key.push(if body.len() < s.len() {
+        HIDDEN
+    } else {
+        REGULAR
+    });
+    // These are the same three steps as compare_bytes: the name without its extension, the
+    // name with its extension and finally the bytes of the name as the tie-break. Only the last
+    // part is not self-delimiting, which is fine since nothing follows it.
+    encode_sequence(&mut key, split_ext(body).0);
+    encode_sequence(&mut key, body);
+    key.extend_from_slice(body);
+    key
+}
+
+// encode_sequence appends the non-digit and digit sequences of s in an order preserving way.
+fn encode_sequence(key: &mut Vec<u8>, s: &[u8]) {
+    let (non_digit, rest) = non_digit_seq(s);
+    let (digit, rest) = digit_seq(rest);
+    // A string that only holds a zero compares equal to the empty string, so both need the same
+    // encoding. Only the first segment can have an empty non-digit sequence.
+    if non_digit.is_empty() && rest.is_empty() && strip_leading_zeros(digit).is_empty() {
+        key.extend_from_slice(&SEQUENCE_END);
+        return;
+    }
+    encode_segment(key, non_digit, digit);
+    let mut remaining = rest;
+    while !remaining.is_empty() {
+        let (non_digit, rest) = non_digit_seq(remaining);
+        let (digit, rest) = digit_seq(rest);
+        encode_segment(key, non_digit, digit);
+        remaining = rest;
+    }
+    key.extend_from_slice(&SEQUENCE_END);
+}
+
+fn encode_segment(key: &mut Vec<u8>, non_digit: &[u8], digit: &[u8]) {
+    key.extend(non_digit.iter().map(|&c| WEIGHTS[c as usize]));
+    key.push(END_WEIGHT);
+
+    // Numbers are prefixed by their length without leading zeros, so that longer numbers sort
+    // after shorter ones. The length is written as a run of 0xFF bytes followed by the remainder,
+    // which keeps it order preserving for numbers of any size.
+    let digit = strip_leading_zeros(digit);
+    let mut len = digit.len();
+    while len >= 0xFF {
+        key.push(0xFF);
+        len -= 0xFF;
+    }
+    key.push(len as u8);
+    key.extend_from_slice(digit);
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use core::cmp::Ordering;
+
+    use proptest::prelude::*;
+    use test_case::test_case;
+
+    use super::*;
+    use crate::compare_bytes;
+    use crate::test::GNULIB_SORTED;
+
+    #[test]
+    fn test_gnulib_list() {
+        for a in GNULIB_SORTED {
+            for b in GNULIB_SORTED {
+                assert_eq!(
+                    sort_key(a).cmp(&sort_key(b)),
+                    compare_bytes(a.as_bytes(), b.as_bytes()),
+                    "comparing {:?} and {:?}",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    #[test_case("0~", "0"; "leading zero followed by tilde")]
+    #[test_case("0", "0a"; "leading zero followed by letter")]
+    #[test_case("a0", "a00"; "zeros tie break on bytes")]
+    #[test_case("a~", "a"; "tilde before end")]
+    #[test_case("1", "18446744073709551616"; "above u64 max")]
+    #[test_case(".0~", ".0"; "hidden leading zero followed by tilde")]
+    fn test_ordering(a: &str, b: &str) {
+        assert_eq!(compare_bytes(a.as_bytes(), b.as_bytes()), Ordering::Less);
+        assert!(sort_key(a) < sort_key(b));
+    }
+
+    #[test]
+    fn test_long_numbers() {
+        let short = "9".repeat(300);
+        let long = format!("1{}", "0".repeat(300));
+        assert_eq!(
+            compare_bytes(short.as_bytes(), long.as_bytes()),
+            Ordering::Less
+        );
+        assert!(sort_key(&short) < sort_key(&long));
+    }
+
+    // Names built from bytes that exercise every rule: tildes, letters, other bytes, periods for
+    // hidden files and extensions, zeros and invalid UTF-8.
+    fn name() -> impl Strategy<Value = Vec<u8>> {
+        let bytes = b"~.aAz019_-\x01\xe9".to_vec();
+        prop::collection::vec(prop::sample::select(bytes), 0..10)
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(10_000))]
+        #[test]
+        fn test_sort_key_matches_compare(a in name(), b in name()) {
+            prop_assert_eq!(sort_key_bytes(&a).cmp(&sort_key_bytes(&b)), compare_bytes(&a, &b));
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use core::cmp::Ordering;
+
+// This is a port of glibc's strverscmp. It walks both strings with a small state machine that
+// tracks whether the current position is in a normal part, an integral part or a fractional part
+// (a number with leading zeros).
+// https://sourceware.org/git/?p=glibc.git;a=blob;f=string/strverscmp.c
+
+// States: S_N is normal, S_I is comparing integral parts, S_F is comparing fractional parts and
+// S_Z is the same as S_F but with only leading zeros seen so far.
+const S_N: usize = 0x0;
+const S_I: usize = 0x3;
+const S_F: usize = 0x6;
+const S_Z: usize = 0x9;
+
+// Result types: CMP returns the difference of the bytes, LEN compares the lengths of the digit
+// sequences first. Every other value is returned as is.
+const CMP: i8 = 2;
+const LEN: i8 = 3;
+
+// The next state, indexed by the current state plus the class of the next byte: other (x), a
+// non-zero digit (d) or a zero (0).
+#[rustfmt::skip]
+const NEXT_STATE: [usize; 12] = [
+    /* state    x    d    0  */
+    /* S_N */  S_N, S_I, S_Z,
+    /* S_I */  S_N, S_I, S_I,
+    /* S_F */  S_N, S_F, S_F,
+    /* S_Z */  S_N, S_F, S_Z,
+];
+
+// The result type, indexed by the state reached with the first differing byte of a and the class
+// of the first differing byte of b.
+#[rustfmt::skip]
+const RESULT_TYPE: [i8; 36] = [
+    /* state   x/x  x/d  x/0  d/x  d/d  d/0  0/x  0/d  0/0  */
+    /* S_N */  CMP, CMP, CMP, CMP, LEN, CMP, CMP, CMP, CMP,
+    /* S_I */  CMP, -1,  -1,  1,   LEN, LEN, 1,   LEN, LEN,
+    /* S_F */  CMP, CMP, CMP, CMP, CMP, CMP, CMP, CMP, CMP,
+    /* S_Z */  CMP, 1,   1,   -1,  CMP, CMP, -1,  CMP, CMP,
+];
+
+/// strverscmp compares two strings like glibc's `strverscmp`, which is what `versionsort` in
+/// `scandir` and older builds of `ls -v` use.
+///
+/// Unlike `compare`, numbers with leading zeros are treated as fractional parts, so "01" sorts
+/// before "1" and "010" sorts before "09". There are no special rules for hidden files, file
+/// extensions or tildes.
+/// # Examples
+/// ```
+/// use std::cmp::Ordering;
+/// use vsort::strverscmp;
+///
+/// fn main() {
+///     let mut names = vec!["10", "9", "1", "0", "09", "010", "01", "00", "000"];
+///     names.sort_by(|a, b| strverscmp(a, b));
+///     assert_eq!(names, vec!["000", "00", "01", "010", "09", "0", "1", "9", "10"]);
+///
+///     assert_eq!(strverscmp("1.01", "1.1"), Ordering::Less);
+/// }
+/// ```
+pub fn strverscmp(a: &str, b: &str) -> Ordering {
+    strverscmp_bytes(a.as_bytes(), b.as_bytes())
+}
+
+/// strverscmp_bytes is like `strverscmp`, but for byte strings that do not need to be valid UTF-8.
+/// As in C, a NUL byte ends the string.
+pub fn strverscmp_bytes(a: &[u8], b: &[u8]) -> Ordering {
+    // Reading past the end of a slice behaves like reading the terminating NUL of a C string.
+    let at = |s: &[u8], i: usize| s.get(i).copied().unwrap_or(0);
+
+    let mut i = 0;
+    let mut c1 = at(a, i);
+    let mut c2 = at(b, i);
+    let mut state = S_N + class(c1);
+    while c1 == c2 {
+        if c1 == 0 {
+            return Ordering::Equal;
+        }
+        state = NEXT_STATE[state];
+        i += 1;
+        c1 = at(a, i);
+        c2 = at(b, i);
+        state += class(c1);
+    }
+    let diff = c1.cmp(&c2);
+
+    match RESULT_TYPE[state * 3 + class(c2)] {
+        CMP => diff,
+        LEN => {
+            // The longer digit sequence is the larger number. If both have the same length
+            // the first differing digit decides.
+            let mut j = i;
+            loop {
+                let a_digit = at(a, j).is_ascii_digit();
+                let b_digit = at(b, j).is_ascii_digit();
+                match (a_digit, b_digit) {
+                    (true, true) => j += 1,
+                    (true, false) => return Ordering::Greater,
+                    (false, true) => return Ordering::Less,
+                    (false, false) => return diff,
+                }
+            }
+        }
+        result => result.cmp(&0),
+    }
+}
+
+// class returns the column for the given byte in the state tables: 0 for non-digits, 1 for
+// non-zero digits and 2 for zero.
+fn class(c: u8) -> usize {
+    usize::from(c == b'0') + usize::from(c.is_ascii_digit())
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    // These tests are lifted from
+    // https://github.com/coreutils/gnulib/blob/master/tests/test-strverscmp.c
+    #[test_case("", "", Ordering::Equal; "empty strings")]
+    #[test_case("a", "a", Ordering::Equal; "equal strings")]
+    #[test_case("a", "b", Ordering::Less; "letters")]
+    #[test_case("b", "a", Ordering::Greater; "letters reversed")]
+    #[test_case("000", "00", Ordering::Less; "more leading zeros first")]
+    #[test_case("00", "000", Ordering::Greater; "more leading zeros first reversed")]
+    #[test_case("a0", "a", Ordering::Greater; "trailing zero")]
+    #[test_case("00", "01", Ordering::Less; "fractional parts")]
+    #[test_case("01", "010", Ordering::Less; "longer fractional part")]
+    #[test_case("010", "09", Ordering::Less; "fractional parts compare like decimals")]
+    #[test_case("09", "0", Ordering::Less; "fractional before integral")]
+    #[test_case("9", "10", Ordering::Less; "integral parts")]
+    #[test_case("0a", "0", Ordering::Greater; "zero followed by a letter")]
+    // From glibc bug 9913.
+    #[test_case("B0075022800016.gbp.corp.com", "B007502280067.gbp.corp.com", Ordering::Less; "bug 9913 a b")]
+    #[test_case("B007502280067.gbp.corp.com", "B007502357019.GBP.CORP.COM", Ordering::Less; "bug 9913 b c")]
+    #[test_case("B0075022800016.gbp.corp.com", "B007502357019.GBP.CORP.COM", Ordering::Less; "bug 9913 a c")]
+    fn test_gnulib_vectors(a: &str, b: &str, expected: Ordering) {
+        assert_eq!(strverscmp(a, b), expected);
+        assert_eq!(strverscmp(b, a), expected.reverse());
+    }
+
+    // The example from the strverscmp(3) man page.
+    #[test]
+    fn test_man_page_order() {
+        let expected = vec!["000", "00", "01", "010", "09", "0", "1", "9", "10"];
+        let mut list = expected.clone();
+        list.reverse();
+        list.sort_by(|a, b| strverscmp(a, b));
+        assert_eq!(list, expected);
+    }
+
+    // These pairs show where strverscmp and GNU version sort disagree.
+    #[test_case("1.010", "1.1", Ordering::Less; "fractional part")]
+    #[test_case("1.0~rc1", "1.0", Ordering::Greater; "tilde")]
+    #[test_case("a.b", "a+", Ordering::Greater; "letters before other bytes")]
+    fn test_differences_with_compare(a: &str, b: &str, expected: Ordering) {
+        assert_eq!(strverscmp(a, b), expected);
+        assert_eq!(crate::compare(a, b), expected.reverse());
+    }
+
+    #[test]
+    fn test_nul_ends_the_string() {
+        assert_eq!(strverscmp_bytes(b"a1\0b", b"a1\0c"), Ordering::Equal);
+        assert_eq!(strverscmp_bytes(b"a1\0b", b"a1"), Ordering::Equal);
+    }
+}
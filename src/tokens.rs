@@ -0,0 +1,189 @@
+use core::iter::FusedIterator;
+use core::ops::Range;
+
+use crate::{digit_seq, non_digit_seq, split_ext};
+
+/// Token is a run of non-digit or digit characters, as split by GNU version sort. Each token
+/// knows its byte span in the string it was taken from.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Token<'a> {
+    /// NonDigit is a run of characters that are not ASCII digits. They are compared character by
+    /// character, with the tilde first and ASCII letters before all other characters.
+    NonDigit { text: &'a str, span: Range<usize> },
+    /// Digit is a run of ASCII digits. They are compared by their numerical value.
+    Digit { text: &'a str, span: Range<usize> },
+}
+
+impl<'a> Token<'a> {
+    /// as_str returns the text of the token.
+    pub fn as_str(&self) -> &'a str {
+        match self {
+            Token::NonDigit { text, .. } | Token::Digit { text, .. } => text,
+        }
+    }
+
+    /// span returns the byte range of the token in the original string.
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            Token::NonDigit { span, .. } | Token::Digit { span, .. } => span.clone(),
+        }
+    }
+
+    /// is_digit returns true if the token is a run of digits.
+    pub fn is_digit(&self) -> bool {
+        matches!(self, Token::Digit { .. })
+    }
+}
+
+/// tokens splits the given string into the non-digit and digit runs that GNU version sort
+/// compares. The runs alternate, so a string that starts with a digit starts with a `Digit`
+/// token. It does not allocate.
+///
+/// `compare` applies these runs to the name without the leading period of hidden files and, as a
+/// first pass, without the file extension. Use `split_extension` to get those parts.
+/// # Examples
+/// ```
+/// use vsort::{tokens, Token};
+///
+/// fn main() {
+///     let tokens: Vec<Token> = tokens("gcc-10.2").collect();
+///     assert_eq!(
+///         tokens,
+///         vec![
+///             Token::NonDigit { text: "gcc-", span: 0..4 },
+///             Token::Digit { text: "10", span: 4..6 },
+///             Token::NonDigit { text: ".", span: 6..7 },
+///             Token::Digit { text: "2", span: 7..8 },
+///         ]
+///     );
+/// }
+/// ```
+pub fn tokens(s: &str) -> Tokens<'_> {
+    Tokens { s, start: 0 }
+}
+
+/// Tokens is the iterator returned by `tokens`.
+#[derive(Clone, Debug)]
+pub struct Tokens<'a> {
+    s: &'a str,
+    start: usize,
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = &self.s.as_bytes()[self.start..];
+        let is_digit = rest.first()?.is_ascii_digit();
+        let len = if is_digit {
+            digit_seq(rest).0.len()
+        } else {
+            non_digit_seq(rest).0.len()
+        };
+        // ASCII digits never split a UTF-8 character, so both ends are character boundaries.
+        let span = self.start..self.start + len;
+        let text = &self.s[span.clone()];
+        self.start = span.end;
+        Some(if is_digit {
+            Token::Digit { text, span }
+        } else {
+            Token::NonDigit { text, span }
+        })
+    }
+}
+
+impl FusedIterator for Tokens<'_> {}
+
+/// FileNameParts is a file name split into the parts that GNU version sort compares.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FileNameParts<'a> {
+    /// hidden is true if the name starts with a period and is not one of the special names "."
+    /// and "..". Hidden files sort before all other names except the special names.
+    pub hidden: bool,
+    /// stem is the name without the leading period of hidden files and without the extension.
+    pub stem: &'a str,
+    /// extension is the file extension including its leading period, or empty if there is none.
+    pub extension: &'a str,
+}
+
+/// split_extension splits a file name into its parts. An extension is a period followed by an
+/// ASCII letter or tilde and any number of ASCII letters, digits or tildes, repeated any number
+/// of times at the end of the name, so `hello-8.0.12.tar.gz` has the extension `.tar.gz`.
+/// # Examples
+/// ```
+/// use vsort::{split_extension, FileNameParts};
+///
+/// fn main() {
+///     assert_eq!(
+///         split_extension(".hello-8.0.12.tar.gz"),
+///         FileNameParts { hidden: true, stem: "hello-8.0.12", extension: ".tar.gz" }
+///     );
+/// }
+/// ```
+pub fn split_extension(name: &str) -> FileNameParts<'_> {
+    let (hidden, body) = match name {
+        "" | "." | ".." => (false, name),
+        _ => match name.strip_prefix('.') {
+            Some(body) => (true, body),
+            None => (false, name),
+        },
+    };
+    let stem_len = split_ext(body.as_bytes()).0.len();
+    // Extensions only start at an ASCII period, which is always a character boundary.
+    let (stem, extension) = body.split_at(stem_len);
+    FileNameParts {
+        hidden,
+        stem,
+        extension,
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+
+    #[test_case("", &[]; "empty")]
+    #[test_case("abc", &["abc"]; "only non-digits")]
+    #[test_case("123", &["123"]; "only digits")]
+    #[test_case("1.0~rc1", &["1", ".", "0", "~rc", "1"]; "starts with digit")]
+    #[test_case("αβγ10δ", &["αβγ", "10", "δ"]; "non-ascii")]
+    #[test_case("a007b", &["a", "007", "b"]; "leading zeros are kept")]
+    fn test_tokens(input: &str, expected: &[&str]) {
+        let tokens: Vec<Token> = tokens(input).collect();
+        let texts: Vec<&str> = tokens.iter().map(Token::as_str).collect();
+        assert_eq!(texts, expected);
+        for (i, token) in tokens.iter().enumerate() {
+            assert_eq!(&input[token.span()], token.as_str());
+            assert_eq!(
+                token.is_digit(),
+                token.as_str().as_bytes()[0].is_ascii_digit()
+            );
+            if let Some(next) = tokens.get(i + 1) {
+                assert_eq!(token.span().end, next.span().start);
+                assert_ne!(token.is_digit(), next.is_digit());
+            }
+        }
+    }
+
+    #[test_case("hello-8.txt", false, "hello-8", ".txt"; "basic")]
+    #[test_case("hello-8.2", false, "hello-8.2", ""; "without extension")]
+    #[test_case(".bashrc", true, "bashrc", ""; "hidden")]
+    #[test_case(".autom4te.cfg", true, "autom4te", ".cfg"; "hidden with extension")]
+    #[test_case("", false, "", ""; "empty")]
+    #[test_case(".", false, ".", ""; "dot")]
+    #[test_case("..", false, "..", ""; "dot dot")]
+    #[test_case("...", true, "..", ""; "three dots")]
+    #[test_case("café.txt", false, "café", ".txt"; "non-ascii")]
+    fn test_split_extension(name: &str, hidden: bool, stem: &str, extension: &str) {
+        assert_eq!(
+            split_extension(name),
+            FileNameParts {
+                hidden,
+                stem,
+                extension
+            }
+        );
+    }
+}
@@ -0,0 +1,296 @@
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::ops::Range;
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{compare_digit_seq, compare_non_digit_seq, digit_seq, non_digit_seq, split_ext};
+
+/// Version is a file name that has been tokenized ahead of time for GNU version sort.
+///
+/// `compare` splits both names into non-digit and digit sequences on every call, once without
+/// the file extension and once with it. A `Version` does that work once, which pays off when
+/// the same names are compared many times, e.g. when sorting large lists. Its `Ord`
+/// implementation is consistent with `compare_bytes`.
+/// # Examples
+/// ```
+/// use vsort::Version;
+///
+/// fn main() {
+///     let mut file_names = vec![
+///         String::from("b 10.txt"),
+///         String::from("b 5.txt"),
+///         String::from("a.txt"),
+///     ];
+///
+///     // The cached key has to own its data, so convert the borrowed version.
+///     file_names.sort_by_cached_key(|name| Version::new(name).into_owned());
+///     assert_eq!(file_names, vec!["a.txt", "b 5.txt", "b 10.txt"]);
+///
+///     assert!(Version::new("1.0~rc1") < Version::new("1.0"));
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Version<'a> {
+    name: Cow<'a, [u8]>,
+    kind: Kind,
+    // The segments of the name without the leading period and without the file extension.
+    stem: Vec<Segment>,
+    // The segments of the name without the leading period.
+    full: Vec<Segment>,
+}
+
+// The declaration order matches the priority given to each kind of name by the spec: the
+// special names ("", ".", "..") come first, then hidden files, then everything else.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Kind {
+    Empty,
+    Dot,
+    DotDot,
+    Hidden,
+    Regular,
+}
+
+// A segment is a non-digit sequence followed by a digit sequence, stored as byte ranges into
+// the name. Leading zeros are stripped from the digit range since they never affect ordering.
+#[derive(Clone, Debug)]
+struct Segment {
+    non_digit: Range<usize>,
+    digit: Range<usize>,
+}
+
+impl<'a> Version<'a> {
+    /// new tokenizes the given string.
+    pub fn new(s: &'a str) -> Self {
+        Self::from_bytes(s.as_bytes())
+    }
+
+    /// from_bytes tokenizes the given bytes, which do not need to be valid UTF-8.
+    pub fn from_bytes(s: &'a [u8]) -> Self {
+        Self::from_cow(Cow::Borrowed(s))
+    }
+
+    fn from_cow(name: Cow<'a, [u8]>) -> Self {
+        let kind = match &*name {
+            b"" => Kind::Empty,
+            b"." => Kind::Dot,
+            b".." => Kind::DotDot,
+            s if s.starts_with(b".") => Kind::Hidden,
+            _ => Kind::Regular,
+        };
+        let (stem, full) = match kind {
+            Kind::Empty | Kind::Dot | Kind::DotDot => (Vec::new(), Vec::new()),
+            Kind::Hidden | Kind::Regular => {
+                let offset = if kind == Kind::Hidden { 1 } else { 0 };
+                let body = &name[offset..];
+                let stem_len = split_ext(body).0.len();
+                (segments(&body[..stem_len], offset), segments(body, offset))
+            }
+        };
+        Self {
+            name,
+            kind,
+            stem,
+            full,
+        }
+    }
+
+    /// into_owned converts the version into one that owns its data, e.g. to use it as the key
+    /// of `sort_by_cached_key`.
+    pub fn into_owned(self) -> Version<'static> {
+        Version {
+            name: Cow::Owned(self.name.into_owned()),
+            kind: self.kind,
+            stem: self.stem,
+            full: self.full,
+        }
+    }
+
+    /// as_bytes returns the original name.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.name
+    }
+
+    /// is_hidden returns true if the name starts with a period and is not one of the special
+    /// names "." and "..".
+    pub fn is_hidden(&self) -> bool {
+        self.kind == Kind::Hidden
+    }
+
+    /// extension returns the file extension as defined by GNU version sort, including the
+    /// leading period. It is empty if the name has no extension.
+    pub fn extension(&self) -> &[u8] {
+        match self.stem.last() {
+            Some(last) => &self.name[last.digit.end..],
+            None => match self.kind {
+                Kind::Hidden => &self.name[1..],
+                _ => b"",
+            },
+        }
+    }
+
+    // The name without the leading period of hidden files.
+    fn body(&self) -> &[u8] {
+        match self.kind {
+            Kind::Hidden => &self.name[1..],
+            _ => &self.name,
+        }
+    }
+
+    fn segments_cmp(&self, a: &[Segment], other: &Self, b: &[Segment]) -> Ordering {
+        // A missing segment compares like an empty non-digit sequence followed by a zero, which
+        // is how sequence_cmp treats a string that has been exhausted.
+        for i in 0..a.len().max(b.len()) {
+            let (a_non_digit, a_digit) = self.segment(a.get(i));
+            let (b_non_digit, b_digit) = other.segment(b.get(i));
+            let cmp = compare_non_digit_seq(a_non_digit, b_non_digit, false)
+                .then_with(|| compare_digit_seq(a_digit, b_digit));
+            if cmp != Ordering::Equal {
+                return cmp;
+            }
+        }
+        Ordering::Equal
+    }
+
+    fn segment(&self, segment: Option<&Segment>) -> (&[u8], &[u8]) {
+        segment.map_or((b"", b""), |s| {
+            (&self.name[s.non_digit.clone()], &self.name[s.digit.clone()])
+        })
+    }
+}
+
+// segments splits s into its non-digit and digit sequences. The ranges are shifted by offset so
+// that they index into the full name.
+fn segments(s: &[u8], offset: usize) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut remaining = s;
+    while !remaining.is_empty() {
+        let start = offset + s.len() - remaining.len();
+        let (non_digit, rest) = non_digit_seq(remaining);
+        let (digit, rest) = digit_seq(rest);
+        let zeros = digit.iter().take_while(|&&c| c == b'0').count();
+        let digit_start = start + non_digit.len();
+        segments.push(Segment {
+            non_digit: start..digit_start,
+            digit: digit_start + zeros..digit_start + digit.len(),
+        });
+        remaining = rest;
+    }
+    segments
+}
+
+impl<'a> From<&'a str> for Version<'a> {
+    fn from(s: &'a str) -> Self {
+        Self::new(s)
+    }
+}
+
+impl<'a> From<&'a [u8]> for Version<'a> {
+    fn from(s: &'a [u8]) -> Self {
+        Self::from_bytes(s)
+    }
+}
+
+impl From<String> for Version<'static> {
+    fn from(s: String) -> Self {
+        Self::from_cow(Cow::Owned(s.into_bytes()))
+    }
+}
+
+impl From<Vec<u8>> for Version<'static> {
+    fn from(s: Vec<u8>) -> Self {
+        Self::from_cow(Cow::Owned(s))
+    }
+}
+
+impl Ord for Version<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let cmp = self.kind.cmp(&other.kind);
+        if cmp != Ordering::Equal {
+            return cmp;
+        }
+        match self.kind {
+            // There is only one name of each special kind.
+            Kind::Empty | Kind::Dot | Kind::DotDot => Ordering::Equal,
+            Kind::Hidden | Kind::Regular => self
+                .segments_cmp(&self.stem, other, &other.stem)
+                .then_with(|| self.segments_cmp(&self.full, other, &other.full))
+                .then_with(|| self.body().cmp(other.body())),
+        }
+    }
+}
+
+impl PartialOrd for Version<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Two versions only compare as equal when their names are identical, so equality and hashing
+// can use the names directly.
+impl PartialEq for Version<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for Version<'_> {}
+
+impl Hash for Version<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use test_case::test_case;
+
+    use super::*;
+    use crate::compare_bytes;
+    use crate::test::GNULIB_SORTED;
+
+    #[test]
+    fn test_ord_matches_compare() {
+        for a in GNULIB_SORTED {
+            for b in GNULIB_SORTED {
+                assert_eq!(
+                    Version::new(a).cmp(&Version::new(b)),
+                    compare_bytes(a.as_bytes(), b.as_bytes()),
+                    "comparing {:?} and {:?}",
+                    a,
+                    b
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_sort_by_cached_key() {
+        let mut list: Vec<String> = GNULIB_SORTED.iter().rev().map(|s| s.to_string()).collect();
+        list.sort_by_cached_key(|s| Version::new(s).into_owned());
+        assert_eq!(list, GNULIB_SORTED);
+    }
+
+    #[test_case("a", "a0"; "zeros are the same as empty string")]
+    #[test_case("a0001", "a1"; "leading zeros")]
+    #[test_case(".a01.txt", ".a1.txt"; "hidden with extension")]
+    fn test_tie_break_on_bytes(a: &str, b: &str) {
+        assert_eq!(Version::new(a).cmp(&Version::new(b)), Ordering::Less);
+        assert_ne!(Version::new(a), Version::new(b));
+    }
+
+    #[test_case("hello-8.0.12.tar.gz", false, ".tar.gz"; "with extension")]
+    #[test_case("hello-8.2", false, ""; "without extension")]
+    #[test_case(".autom4te.cfg", true, ".cfg"; "hidden with extension")]
+    #[test_case(".txt", true, ""; "hidden without extension")]
+    #[test_case(".", false, ""; "dot is not hidden")]
+    fn test_metadata(name: &str, hidden: bool, extension: &str) {
+        let version = Version::new(name);
+        assert_eq!(version.is_hidden(), hidden);
+        assert_eq!(version.extension(), extension.as_bytes());
+        assert_eq!(version.as_bytes(), name.as_bytes());
+    }
+}
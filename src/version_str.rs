@@ -0,0 +1,332 @@
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::Deref;
+
+#[cfg(feature = "alloc")]
+use alloc::borrow::{Borrow, ToOwned};
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+
+use crate::compare;
+
+/// VersionStr is a string slice that is ordered by GNU version sort. It relates to
+/// `VersionString` the same way `str` relates to `String`.
+///
+/// Ordered collections keyed by `VersionString` can be queried with a `&VersionStr`, so lookups do
+/// not need to allocate.
+/// # Examples
+/// ```
+/// use vsort::VersionStr;
+///
+/// fn main() {
+///     let mut file_names = [VersionStr::new("b 10.txt"), VersionStr::new("b 5.txt")];
+///
+///     file_names.sort();
+///     assert_eq!(file_names, ["b 5.txt", "b 10.txt"]);
+/// }
+/// ```
+// Since compare only returns Ordering::Equal for identical strings, the derived PartialEq, Eq and
+// Hash implementations are consistent with Ord.
+#[derive(PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct VersionStr(str);
+
+impl VersionStr {
+    /// new wraps the given string slice.
+    pub fn new<S: AsRef<str> + ?Sized>(s: &S) -> &VersionStr {
+        let s: &str = s.as_ref();
+        // SAFETY: VersionStr is a #[repr(transparent)] wrapper around str, so both types have
+        // the same layout.
+        unsafe { &*(s as *const str as *const VersionStr) }
+    }
+
+    /// as_str returns the underlying string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Ord for VersionStr {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare(&self.0, &other.0)
+    }
+}
+
+impl PartialOrd for VersionStr {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq<str> for VersionStr {
+    fn eq(&self, other: &str) -> bool {
+        &self.0 == other
+    }
+}
+
+impl PartialEq<&str> for VersionStr {
+    fn eq(&self, other: &&str) -> bool {
+        &self.0 == *other
+    }
+}
+
+impl Deref for VersionStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for VersionStr {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<VersionStr> for VersionStr {
+    fn as_ref(&self) -> &VersionStr {
+        self
+    }
+}
+
+impl AsRef<VersionStr> for str {
+    fn as_ref(&self) -> &VersionStr {
+        VersionStr::new(self)
+    }
+}
+
+impl<'a> From<&'a str> for &'a VersionStr {
+    fn from(s: &'a str) -> Self {
+        VersionStr::new(s)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl ToOwned for VersionStr {
+    type Owned = VersionString;
+
+    fn to_owned(&self) -> VersionString {
+        VersionString(self.0.to_owned())
+    }
+}
+
+impl fmt::Debug for VersionStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for VersionStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// VersionString is an owned string that is ordered by GNU version sort. Use it as the key of a
+/// `BTreeMap` or `BTreeSet` to keep names in version order.
+/// # Examples
+/// ```
+/// use std::collections::{BTreeMap, BTreeSet};
+/// use vsort::{VersionStr, VersionString};
+///
+/// fn main() {
+///     let set: BTreeSet<VersionString> = ["1.10", "1.9", "1.9~rc1"]
+///         .into_iter()
+///         .map(VersionString::from)
+///         .collect();
+///
+///     let versions: Vec<&str> = set.iter().map(|v| v.as_str()).collect();
+///     assert_eq!(versions, vec!["1.9~rc1", "1.9", "1.10"]);
+///
+///     let mut sizes = BTreeMap::new();
+///     sizes.insert(VersionString::from("b 10.txt"), 10);
+///     sizes.insert(VersionString::from("b 5.txt"), 5);
+///     assert_eq!(sizes.get(VersionStr::new("b 5.txt")), Some(&5));
+/// }
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct VersionString(String);
+
+#[cfg(feature = "alloc")]
+impl VersionString {
+    /// new wraps the given string.
+    pub fn new(s: String) -> Self {
+        Self(s)
+    }
+
+    /// as_version_str returns the string as a `VersionStr` slice.
+    pub fn as_version_str(&self) -> &VersionStr {
+        VersionStr::new(&self.0)
+    }
+
+    /// into_string returns the underlying string.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Ord for VersionString {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_version_str().cmp(other.as_version_str())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PartialOrd for VersionString {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PartialEq<str> for VersionString {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl PartialEq<&str> for VersionString {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Deref for VersionString {
+    type Target = VersionStr;
+
+    fn deref(&self) -> &VersionStr {
+        self.as_version_str()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Borrow<VersionStr> for VersionString {
+    fn borrow(&self) -> &VersionStr {
+        self.as_version_str()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl AsRef<str> for VersionString {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl AsRef<VersionStr> for VersionString {
+    fn as_ref(&self) -> &VersionStr {
+        self.as_version_str()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<String> for VersionString {
+    fn from(s: String) -> Self {
+        Self(s)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<&str> for VersionString {
+    fn from(s: &str) -> Self {
+        Self(s.to_owned())
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<&VersionStr> for VersionString {
+    fn from(s: &VersionStr) -> Self {
+        s.to_owned()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<VersionString> for String {
+    fn from(s: VersionString) -> Self {
+        s.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Debug for VersionString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for VersionString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::{BTreeMap, BTreeSet, HashSet};
+    use std::hash::{Hash, Hasher};
+
+    use super::*;
+    use crate::test::GNULIB_SORTED;
+
+    #[test]
+    fn test_btree_set_order() {
+        let set: BTreeSet<VersionString> = GNULIB_SORTED
+            .iter()
+            .rev()
+            .map(|&s| VersionString::from(s))
+            .collect();
+        let list: Vec<&str> = set.iter().map(|s| s.as_str()).collect();
+        assert_eq!(list, GNULIB_SORTED);
+    }
+
+    #[test]
+    fn test_btree_map_lookup_by_borrowed_key() {
+        let mut map = BTreeMap::new();
+        map.insert(VersionString::from("a1"), 1);
+        map.insert(VersionString::from("a01"), 2);
+        map.insert(VersionString::from("a~"), 3);
+
+        assert_eq!(map.get(VersionStr::new("a1")), Some(&1));
+        assert_eq!(map.get(VersionStr::new("a01")), Some(&2));
+        assert_eq!(map.get(VersionStr::new("a")), None);
+        assert_eq!(
+            map.keys().collect::<Vec<_>>(),
+            vec!["a~", "a01", "a1"],
+            "a01 and a1 are equal as versions and tie break on bytes"
+        );
+    }
+
+    #[test]
+    fn test_hash_matches_borrowed_form() {
+        fn hash<T: Hash + ?Sized>(t: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            t.hash(&mut hasher);
+            hasher.finish()
+        }
+        let owned = VersionString::from("gcc-10.fc9.tar.gz");
+        assert_eq!(hash(&owned), hash(owned.as_version_str()));
+
+        let set: HashSet<VersionString> = [owned.clone()].into_iter().collect();
+        assert!(set.contains(VersionStr::new("gcc-10.fc9.tar.gz")));
+    }
+
+    #[test]
+    fn test_conversions() {
+        let owned = VersionString::from("b 5.txt");
+        let borrowed: &VersionStr = &owned;
+        assert_eq!(borrowed.to_owned(), owned);
+        assert_eq!(borrowed.len(), 7);
+        assert_eq!(owned.to_string(), "b 5.txt");
+        assert_eq!(format!("{:?}", borrowed), "\"b 5.txt\"");
+        assert_eq!(String::from(owned), "b 5.txt");
+        assert!(VersionStr::new("b 5.txt") < VersionStr::new("b 10.txt"));
+    }
+}